@@ -0,0 +1,190 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::{env, fs, io};
+
+use crate::Part;
+
+pub const CACHE_FILE: &str = ".aoc_cache";
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct Key {
+    day: u32,
+    part: u8,
+    /// Variant that was requested, not necessarily the one that ran
+    selection: String,
+    input: u64,
+}
+
+/// Answers of the parts from previous runs, stored as tab separated lines.
+/// Entries are only valid for the build that produced them, entries of other
+/// builds are dropped when the cache is loaded.
+pub struct Cache {
+    path: PathBuf,
+    build: u64,
+    entries: HashMap<Key, (String, String)>,
+    changed: bool,
+}
+
+impl Cache {
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let build = build_identity()?;
+        let mut entries = HashMap::new();
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e),
+        };
+        for line in content.lines() {
+            let fields: Vec<&str> = line.split('\t').collect();
+            let [day, part, selection, input, entry_build, variant, answer] = fields[..] else {
+                continue;
+            };
+            let (Ok(day), Ok(part), Ok(input), Ok(entry_build)) = (
+                day.parse(), part.parse(), u64::from_str_radix(input, 16), u64::from_str_radix(entry_build, 16)
+            ) else {
+                continue;
+            };
+            if entry_build != build {
+                continue;
+            }
+            let key = Key { day, part, selection: unescape(selection), input };
+            entries.insert(key, (unescape(variant), unescape(answer)));
+        }
+        Ok(Cache { path: path.to_owned(), build, entries, changed: false })
+    }
+
+    /// Variant that produced the answer and the answer itself.
+    pub fn get(&self, day: u32, part: Part, selection: &str, input: u64) -> Option<&(String, String)> {
+        self.entries.get(&Self::key(day, part, selection, input))
+    }
+
+    pub fn insert(&mut self, day: u32, part: Part, selection: &str, input: u64, variant: &str, answer: &str) {
+        let key = Self::key(day, part, selection, input);
+        let value = (variant.to_owned(), answer.to_owned());
+        if self.entries.get(&key) != Some(&value) {
+            self.entries.insert(key, value);
+            self.changed = true;
+        }
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        if !self.changed {
+            return Ok(());
+        }
+        let mut lines: Vec<String> = self.entries.iter().map(|(key, (variant, answer))| {
+            format!(
+                "{}\t{}\t{}\t{:016x}\t{:016x}\t{}\t{}",
+                key.day, key.part, escape(&key.selection), key.input, self.build, escape(variant), escape(answer)
+            )
+        }).collect();
+        lines.sort();
+        fs::write(&self.path, lines.join("\n") + "\n")
+    }
+
+    fn key(day: u32, part: Part, selection: &str, input: u64) -> Key {
+        let part = match part {
+            Part::One => 1,
+            Part::Two => 2,
+        };
+        Key { day, part, selection: selection.to_owned(), input }
+    }
+}
+
+/// Hash of the input with line endings normalised, so the same input
+/// checked out on different platforms shares the cache entry. The `[day.N]`
+/// parameters change the answers too, so they are part of the hash.
+pub fn hash_input(content: &str, parameters: &[(String, String)]) -> u64 {
+    let mut normalised = content.lines().collect::<Vec<_>>().join("\n");
+    let mut parameters = parameters.to_vec();
+    parameters.sort();
+    for (name, value) in parameters {
+        normalised.push_str(&format!("\0{}={}", name, value));
+    }
+    fnv1a(normalised.as_bytes())
+}
+
+/// Identity of the running build, the hash of it's executable. Any change
+/// of the code produces a new one.
+fn build_identity() -> io::Result<u64> {
+    let executable = fs::read(env::current_exe()?)?;
+    Ok(fnv1a(&executable))
+}
+
+/// FNV-1a, unlike the std hashers it's guaranteed to be stable between runs
+/// and compiler versions.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, b| {
+        (hash ^ *b as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('\t', "\\t").replace('\n', "\\n")
+}
+
+fn unescape(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => result.push('\t'),
+            Some('n') => result.push('\n'),
+            Some(other) => result.push(other),
+            None => result.push('\\'),
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_input_normalises_line_endings() {
+        assert_eq!(hash_input("1,2\r\n3,4\r\n", &[]), hash_input("1,2\n3,4", &[]));
+        assert_ne!(hash_input("1,2\n3,4", &[]), hash_input("1,2\n3,5", &[]));
+    }
+
+    #[test]
+    fn test_hash_input_includes_parameters() {
+        let parameter = |name: &str, value: &str| (name.to_owned(), value.to_owned());
+        let plain = hash_input("1,2", &[]);
+        let ten = hash_input("1,2", &[parameter("connections", "10")]);
+        assert_ne!(plain, ten);
+        assert_ne!(ten, hash_input("1,2", &[parameter("connections", "1000")]));
+        assert_eq!(
+            hash_input("1,2", &[parameter("a", "1"), parameter("b", "2")]),
+            hash_input("1,2", &[parameter("b", "2"), parameter("a", "1")]),
+        );
+    }
+
+    #[test]
+    fn test_escape() {
+        let s = "a\tb\\nc\nd";
+        assert_eq!(unescape(&escape(s)), s);
+        assert!(!escape(s).contains('\n'));
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let path = env::temp_dir().join(format!("aoc_cache_test_{}", std::process::id()));
+        let input = hash_input("R10\nL20", &[]);
+        let mut cache = Cache::load(&path).unwrap();
+        assert_eq!(cache.get(1, Part::One, "default", input), None);
+        cache.insert(1, Part::One, "default", input, "default", "42");
+        cache.insert(1, Part::Two, "fast", input, "fast", "multi\nline");
+        cache.save().unwrap();
+
+        let cache = Cache::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(cache.get(1, Part::One, "default", input), Some(&("default".to_owned(), "42".to_owned())));
+        assert_eq!(cache.get(1, Part::Two, "fast", input), Some(&("fast".to_owned(), "multi\nline".to_owned())));
+        assert_eq!(cache.get(1, Part::Two, "default", input), None);
+        assert_eq!(cache.get(1, Part::One, "default", hash_input("R10", &[])), None);
+    }
+}
@@ -0,0 +1,208 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+use std::{env, fs, io};
+
+use anyhow::{Context, Error, anyhow};
+use serde::{Deserialize, Serialize};
+
+pub const CONFIG_FILE: &str = "aoc.toml";
+const ENV_PREFIX: &str = "AOC_";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    /// Colored report with the times of every step
+    Pretty,
+    /// Tab separated `day part variant answer nanoseconds` lines
+    Compact,
+}
+
+impl FromStr for Format {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pretty" => Ok(Format::Pretty),
+            "compact" => Ok(Format::Compact),
+            _ => Err(anyhow!("Invalid value for format `{}`", s))
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Color {
+    Auto,
+    Always,
+    Never,
+}
+
+impl FromStr for Color {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(Color::Auto),
+            "always" => Ok(Color::Always),
+            "never" => Ok(Color::Never),
+            _ => Err(anyhow!("Invalid value for color `{}`", s))
+        }
+    }
+}
+
+/// Defaults of the runner. Layered from the lowest priority: built in
+/// defaults, `aoc.toml`, `AOC_*` environment variables and the CLI flags.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Days that run when none is given, all of them when empty
+    pub days: Vec<u32>,
+    /// Directory with the `day_XX/{input,example}.txt` files
+    pub input_dir: PathBuf,
+    pub format: Format,
+    pub color: Color,
+    /// Time limit of a single day, in humantime format (`10s`, `1m 30s`)
+    #[serde(with = "humantime_option", skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<Duration>,
+    /// 0 prints only the answers, 1 the times as well, 2 also the inputs
+    pub verbosity: u8,
+    pub cache: bool,
+    /// Puzzle parameters per day, e.g. `[day.8] to_connect = 1000`. TOML
+    /// keys are always strings, so are the days.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub day: BTreeMap<String, BTreeMap<String, toml::Value>>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            days: vec![],
+            input_dir: PathBuf::from("src"),
+            format: Format::Pretty,
+            color: Color::Auto,
+            timeout: None,
+            verbosity: 1,
+            cache: false,
+            day: BTreeMap::new(),
+        }
+    }
+}
+
+impl Config {
+    /// Reads the file on top of the defaults and applies the environment
+    /// variables. A missing file is only an error if it was asked for.
+    pub fn load(path: Option<&Path>) -> anyhow::Result<Self> {
+        let file = path.unwrap_or(Path::new(CONFIG_FILE));
+        let mut config = match fs::read_to_string(file) {
+            Ok(content) => toml::from_str(&content)
+                .with_context(|| format!("Invalid config file {}", file.display()))?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound && path.is_none() => Config::default(),
+            Err(e) => return Err(e).with_context(|| format!("Could not read config file {}", file.display())),
+        };
+        config.apply_env(env::vars())?;
+        Ok(config)
+    }
+
+    fn apply_env(&mut self, vars: impl Iterator<Item = (String, String)>) -> anyhow::Result<()> {
+        for (name, value) in vars {
+            let Some(key) = name.strip_prefix(ENV_PREFIX) else {
+                continue;
+            };
+            let context = || format!("Invalid value of {}", name);
+            match key {
+                "DAYS" => {
+                    self.days = value
+                        .split(',')
+                        .filter(|d| !d.trim().is_empty())
+                        .map(|d| d.trim().parse())
+                        .collect::<Result<_, _>>()
+                        .with_context(context)?;
+                },
+                "INPUT_DIR" => self.input_dir = PathBuf::from(value),
+                "FORMAT" => self.format = value.parse().with_context(context)?,
+                "COLOR" => self.color = value.parse().with_context(context)?,
+                "TIMEOUT" => self.timeout = Some(humantime::parse_duration(&value).with_context(context)?),
+                "VERBOSITY" => self.verbosity = value.parse().with_context(context)?,
+                "CACHE" => self.cache = value.parse().with_context(context)?,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Parameters of the day as strings, ready for `Advent::set_parameter`.
+    pub fn parameters(&self, day: u32) -> Vec<(String, String)> {
+        self.day.get(&day.to_string()).map(|parameters| {
+            parameters.iter().map(|(name, value)| {
+                let value = match value {
+                    toml::Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                };
+                (name.clone(), value)
+            }).collect()
+        }).unwrap_or_default()
+    }
+}
+
+mod humantime_option {
+    use std::time::Duration;
+    use serde::{Deserialize, Deserializer, Serializer, de::Error};
+
+    pub fn serialize<S: Serializer>(value: &Option<Duration>, serializer: S) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(duration) => serializer.serialize_str(&humantime::format_duration(*duration).to_string()),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Duration>, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        humantime::parse_duration(&value).map(Some).map_err(D::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_layer() {
+        let config: Config = toml::from_str(r#"
+            days = [1, 3]
+            format = "compact"
+            timeout = "1m 30s"
+
+            [day.8]
+            to_connect = 1000
+        "#).unwrap();
+        assert_eq!(config.days, vec![1, 3]);
+        assert_eq!(config.format, Format::Compact);
+        assert_eq!(config.timeout, Some(Duration::from_secs(90)));
+        assert_eq!(config.input_dir, PathBuf::from("src"));
+        assert_eq!(config.parameters(8), vec![("to_connect".to_owned(), "1000".to_owned())]);
+        assert_eq!(config.parameters(7), vec![]);
+
+        assert!(toml::from_str::<Config>("colour = \"never\"").is_err());
+    }
+
+    #[test]
+    fn test_env_layer() {
+        let mut config: Config = toml::from_str("days = [1]\nverbosity = 2").unwrap();
+        let vars = [("AOC_DAYS", "4, 5"), ("AOC_COLOR", "never"), ("HOME", "/root")];
+        config.apply_env(vars.iter().map(|(k, v)| (k.to_string(), v.to_string()))).unwrap();
+        assert_eq!(config.days, vec![4, 5]);
+        assert_eq!(config.color, Color::Never);
+        assert_eq!(config.verbosity, 2);
+
+        let vars = [("AOC_FORMAT", "yaml")];
+        assert!(config.apply_env(vars.iter().map(|(k, v)| (k.to_string(), v.to_string()))).is_err());
+    }
+
+    #[test]
+    fn test_print_round_trip() {
+        let mut config = Config { timeout: Some(Duration::from_secs(5)), ..Config::default() };
+        config.day.entry("8".to_owned()).or_default().insert("to_connect".to_owned(), toml::Value::Integer(10));
+        let printed = toml::to_string_pretty(&config).unwrap();
+        assert_eq!(toml::from_str::<Config>(&printed).unwrap(), config);
+    }
+}
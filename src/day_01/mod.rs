@@ -1,3 +1,5 @@
+use crate::utils::random::Rng;
+
 pub struct SecretEntrance {
     rotations: Vec<i32>
 }
@@ -18,6 +20,13 @@ impl crate::Advent for SecretEntrance {
         }
     }
 
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        (0..size).map(|_| {
+            let direction = if rng.chance(1, 2) { 'L' } else { 'R' };
+            format!("{}{}", direction, rng.range(1..=999))
+        }).collect::<Vec<_>>().join("\n")
+    }
+
     fn part_01(&self) -> String {
         let mut counter = 0;
         let mut pos = 50;
@@ -60,7 +69,7 @@ impl SecretEntrance {
         } else if new_pos >= 100 {
             new_counter += 1;
             new_pos -= 100;
-        } else if new_pos == 0 {
+        } else if new_pos == 0 && pos != 0 {
             new_counter += 1;
         }
         // let sign_changes_char = if new_counter != counter {'✓'} else {'×'};
@@ -76,6 +85,9 @@ impl SecretEntrance {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::Advent;
+    use crate::utils::differential::{Differential, shrink_vec};
+    use crate::utils::random::Rng;
 
     #[test]
     fn test_rotation() {
@@ -83,5 +95,31 @@ mod tests {
         assert_eq!(SecretEntrance::rotate(50, 0, -50), (0, 1));
         assert_eq!(SecretEntrance::rotate(50, 0, -75), (75, 1));
         assert_eq!(SecretEntrance::rotate(50, 0, -175), (75, 2));
+        assert_eq!(SecretEntrance::rotate(0, 0, -200), (0, 2));
+    }
+
+    #[test]
+    fn test_password_differential() {
+        let differential = Differential::new(
+            |rng: &mut Rng| (0..rng.range(1..=10)).map(|_| rng.range_isize(-250..=250) as i32).collect::<Vec<i32>>(),
+            |rotations: &Vec<i32>| shrink_vec(rotations, |r| if *r == 0 { vec![] } else { vec![0, r / 2, r - r.signum()] })
+        );
+        // Turns the dial click by click
+        differential.check(
+            |rotations| {
+                let mut pos = 50;
+                let mut counter = 0;
+                for rotation in rotations {
+                    for _ in 0..rotation.abs() {
+                        pos = (pos + rotation.signum()).rem_euclid(100);
+                        if pos == 0 {
+                            counter += 1;
+                        }
+                    }
+                }
+                counter.to_string()
+            },
+            |rotations| SecretEntrance { rotations: rotations.clone() }.part_02()
+        );
     }
-}
\ No newline at end of file
+}
@@ -1,6 +1,8 @@
 use std::cmp;
+use std::collections::HashSet;
 
 use crate::utils::digits_iterator::digits;
+use crate::utils::random::Rng;
 
 pub struct GiftShop {
     ranges: Vec<(usize, usize)>
@@ -21,6 +23,15 @@ impl crate::Advent for GiftShop {
         Self { ranges: ranges }
     }
 
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        (0..size).map(|_| {
+            let len = rng.range(1..=10) as u32;
+            let start = rng.range(10usize.pow(len - 1)..=10usize.pow(len) - 1);
+            let end = start + rng.range(0..=100_000);
+            format!("{}-{}", start, end)
+        }).collect::<Vec<_>>().join(",")
+    }
+
     fn part_01(&self) -> String {
         let mut invalid_ids: Vec<usize> = vec![];
         for range in &self.ranges {
@@ -45,6 +56,13 @@ impl crate::Advent for GiftShop {
         }
         invalid_ids.iter().sum::<usize>().to_string()
     }
+
+    fn variants(&self) -> Vec<crate::Variant<'_>> {
+        vec![
+            crate::Variant::new("generate", crate::Part::One, || self.generate_invalid_ids(true)),
+            crate::Variant::new("generate", crate::Part::Two, || self.generate_invalid_ids(false)),
+        ]
+    }
 }
 
 impl GiftShop {
@@ -159,24 +177,30 @@ impl GiftShop {
     //     true
     // }
 
-    #[allow(dead_code)]
-    fn check_ids(&self) -> String {
-        let mut invalid_ids: Vec<usize> = vec![];
+    /// Instead of checking every id in the range, generates the invalid ids
+    /// from their repeating patterns. An id of length `len` made of a pattern
+    /// of length `pattern_len` is the pattern multiplied by `1..01..01`.
+    /// With `only_halves` the pattern has to be repeated exactly twice.
+    fn generate_invalid_ids(&self, only_halves: bool) -> String {
+        let mut sum = 0;
         for range in &self.ranges {
-            let (max_patterns_i, _prefix_patterns) = GiftShop::get_prefix_patterns(range.0, range.1);
-            if max_patterns_i == 0 {
-                for num in range.0..=range.1 {                    
-                    if Self::check_id_valid_02(num.to_string()) == false {
-                        invalid_ids.push(num);
-                    }                
-                }            
-            } else {
-                // for prefix_pattern in prefix_patterns {
-
-                // }
-            }            
+            let mut invalid_ids: HashSet<usize> = HashSet::new();
+            for (start, end) in Self::get_ranges_by_digits_len(range.0, range.1) {
+                let len = digits(start).count();
+                for pattern_len in 1..=len / 2 {
+                    let repeats = len / pattern_len;
+                    if !len.is_multiple_of(pattern_len) || (only_halves && repeats != 2) {
+                        continue;
+                    }
+                    let multiplier: usize = (0..repeats).map(|i| 10usize.pow((i * pattern_len) as u32)).sum();
+                    let min_pattern = cmp::max(10usize.pow(pattern_len as u32 - 1), start.div_ceil(multiplier));
+                    let max_pattern = cmp::min(10usize.pow(pattern_len as u32) - 1, end / multiplier);
+                    invalid_ids.extend((min_pattern..=max_pattern).map(|pattern| pattern * multiplier));
+                }
+            }
+            sum += invalid_ids.iter().sum::<usize>();
         }
-        invalid_ids.iter().sum::<usize>().to_string()
+        sum.to_string()
     }
 
     #[allow(dead_code)]
@@ -236,6 +260,7 @@ impl GiftShop {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::Advent;
 
     #[test] 
     fn test_ranges_by_digits_len() {
@@ -275,6 +300,13 @@ mod tests {
             }
         }
     }
+    #[test]
+    fn test_generate_invalid_ids() {
+        let shop = GiftShop { ranges: vec![(11, 22), (95, 115), (998, 1012), (1188511880, 1188511890), (5, 1500)] };
+        assert_eq!(shop.generate_invalid_ids(true), shop.part_01());
+        assert_eq!(shop.generate_invalid_ids(false), shop.part_02());
+    }
+
     #[test]
     fn test_common_digits() {
         let lhs = digits(1188511880).collect();
@@ -1,5 +1,9 @@
+use crate::utils::coordinate_system::cartesian::Grid;
+use crate::utils::random::Rng;
+
 pub struct Lobby {
-    banks: Vec<Vec<u8>>
+    /// Every row is a bank of batteries
+    banks: Grid<u8>
 }
 
 impl crate::Advent for Lobby {
@@ -7,13 +11,19 @@ impl crate::Advent for Lobby {
         where 
             Self: Sized {
         
-        let banks: Vec<Vec<u8>> = data.lines().map(|l| l.chars().map(|c| c.to_digit(10).unwrap() as u8).collect()).collect();
+        let banks = Grid::parse(data).unwrap();
         Self { banks }
     }
 
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        (0..size).map(|_| {
+            (0..100).map(|_| char::from_digit(rng.range(1..=9) as u32, 10).unwrap()).collect::<String>()
+        }).collect::<Vec<_>>().join("\n")
+    }
+
     fn part_01(&self) -> String {
         let mut results: Vec<u64> = vec![];
-        for bank in &self.banks {
+        for bank in self.banks.rows() {
             let max_joltage = Self::get_largest_overload_joltage(bank, 2);
             results.push(max_joltage);
         }
@@ -22,7 +32,7 @@ impl crate::Advent for Lobby {
 
     fn part_02(&self) -> String {        
         let mut results: Vec<u64> = vec![];
-        for bank in &self.banks {
+        for bank in self.banks.rows() {
             let max_joltage = Self::get_largest_overload_joltage(bank, 12);
             results.push(max_joltage);
         }
@@ -31,7 +41,7 @@ impl crate::Advent for Lobby {
 }
 
 impl Lobby {
-    fn get_largest_overload_joltage(bank: &Vec<u8>, batteries_count: usize) -> u64 {
+    fn get_largest_overload_joltage(bank: &[u8], batteries_count: usize) -> u64 {
         let mut batteries: Vec<u8> = vec![];
         let mut current_max = 0;
         let mut next_index = 0;
@@ -66,6 +76,9 @@ impl Lobby {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use itertools::Itertools;
+    use crate::utils::differential::{Differential, shrink_vec};
+    use crate::utils::random::Rng;
 
     fn s_to_vec(s: &str) -> Vec<u8> {
         s.chars().map(|c| c.to_digit(10).unwrap() as u8).collect()
@@ -86,4 +99,36 @@ mod tests {
         assert_eq!(Lobby::get_largest_overload_joltage(&s_to_vec("234234234234278"), 12), 434234234278);
         assert_eq!(Lobby::get_largest_overload_joltage(&s_to_vec("818181911112111"), 12), 888911112111);        
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_overload_differential() {
+        let differential = Differential::new(
+            |rng: &mut Rng| {
+                let bank: Vec<u8> = (0..rng.range(1..=12)).map(|_| rng.range(1..=9) as u8).collect();
+                let batteries = rng.range(1..=bank.len());
+                (bank, batteries)
+            },
+            |(bank, batteries): &(Vec<u8>, usize)| {
+                let mut candidates: Vec<(Vec<u8>, usize)> = shrink_vec(bank, |d| (1..*d).collect())
+                    .into_iter()
+                    .filter(|b| b.len() >= *batteries)
+                    .map(|b| (b, *batteries))
+                    .collect();
+                if *batteries > 1 {
+                    candidates.push((bank.clone(), batteries - 1));
+                }
+                candidates
+            }
+        );
+        differential.check(
+            |(bank, batteries)| {
+                bank.iter()
+                    .combinations(*batteries)
+                    .map(|c| c.into_iter().fold(0u64, |num, d| num * 10 + *d as u64))
+                    .max()
+                    .unwrap()
+            },
+            |(bank, batteries)| Lobby::get_largest_overload_joltage(bank, *batteries)
+        );
+    }
+}
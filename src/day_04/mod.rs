@@ -1,7 +1,30 @@
-use crate::utils::coordinate_system::cartesian::Grid;
+use crate::utils::coordinate_system::cartesian::{Automaton, BitGrid, Grid, FromCell, ToCell, Neighbourhood};
+use crate::utils::random::Rng;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Tile { Roll, Empty }
+
+impl FromCell for Tile {
+    fn from_cell(c: char) -> Option<Self> {
+        match c {
+            '@' => Some(Tile::Roll),
+            '.' => Some(Tile::Empty),
+            _ => None
+        }
+    }
+}
+
+impl ToCell for Tile {
+    fn to_cell(&self) -> char {
+        match self {
+            Tile::Roll => '@',
+            Tile::Empty => '.',
+        }
+    }
+}
 
 pub struct PrintingDepartment {
-    grid: Grid<char>
+    grid: Grid<Tile>
 }
 
 impl crate::Advent for PrintingDepartment {
@@ -9,47 +32,118 @@ impl crate::Advent for PrintingDepartment {
         where 
             Self: Sized {
         
-        let points: Vec<Vec<char>> = data.lines().map(|l| l.chars().collect()).collect();
-        let grid = Grid::new(points);
+        let grid = Grid::parse(data).unwrap();
         Self { grid }
     }
 
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        (0..size).map(|_| {
+            (0..size).map(|_| if rng.chance(2, 3) { Tile::Roll } else { Tile::Empty }.to_cell()).collect::<String>()
+        }).collect::<Vec<_>>().join("\n")
+    }
+
     fn part_01(&self) -> String {
-        let counter = self.grid
-            .iter_points()
-            .filter(|p| *p.value == '@')
-            .filter(|p| {
-                let adjacent_coords = self.grid.adjacent_coords(&p.coord);
-                let num_full = adjacent_coords.iter().filter(|c| *self.grid.get_val(c) == '@').count();
+        let offsets = self.grid.neighbour_offsets(Neighbourhood::Adjacent);
+        let counter = self.grid.map
+            .iter()
+            .enumerate()
+            .filter(|(_, tile)| **tile == Tile::Roll)
+            .filter(|(i, _)| {
+                let num_full = offsets.neighbours(*i).filter(|n| self.grid.map[*n] == Tile::Roll).count();
                 num_full < 4
             }).count();
         counter.to_string()
     }
 
     fn part_02(&self) -> String {
-        let mut grid = self.grid.clone();
+        let mut automaton = Automaton::new(self.grid.clone(), Neighbourhood::Adjacent, |tile, neighbours| {
+            if *tile == Tile::Roll && neighbours.count(&Tile::Roll) < 4 {
+                Tile::Empty
+            } else {
+                *tile
+            }
+        }).active_frontier(true);
+        automaton.run_until_fixpoint().to_string()
+    }
+
+    fn variants(&self) -> Vec<crate::Variant<'_>> {
+        vec![
+            crate::Variant::new("bit_grid", crate::Part::One, || self.accessible_bits(false)),
+            crate::Variant::new("bit_grid", crate::Part::Two, || self.accessible_bits(true)),
+        ]
+    }
+}
+
+impl PrintingDepartment {
+    /// Rolls with less than 4 neighbouring rolls, counted word-wise on a
+    /// bit packed grid. Keeps removing them until none are left if `repeat`.
+    fn accessible_bits(&self, repeat: bool) -> String {
+        let mut rolls = BitGrid::from_grid(&self.grid, |tile| *tile == Tile::Roll);
         let mut counter = 0;
         loop {
-            let to_remove: Vec<_> =  grid
-            .iter_points()
-            .filter(|p| *p.value == '@')
-            .filter(|p| {
-                let adjacent_coords = grid.adjacent_coords(&p.coord);
-                let num_full = adjacent_coords.iter().filter(|c| *grid.get_val(c) == '@').count();
-                num_full < 4
-            })
-            .map(|p| p.coord.clone())
-            .collect();
-
-            if to_remove.len() == 0 {
+            let accessible = rolls.and(&rolls.neighbour_counts(Neighbourhood::Adjacent).less_than(4));
+            let removed = accessible.count_ones();
+            counter += removed;
+            if !repeat || removed == 0 {
                 break;
             }
-            
+            rolls = rolls.and_not(&accessible);
+        }
+        counter.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use super::*;
+    use crate::Advent;
+    use crate::utils::coordinate_system::cartesian::Coord;
+
+    /// Removal rounds, every round removes the rolls with less than 4
+    /// neighbouring rolls as counted by `rolls_around`.
+    fn remove_rounds(mut grid: Grid<Tile>, rolls_around: impl Fn(&Grid<Tile>, &Coord<usize>) -> usize) -> usize {
+        let mut counter = 0;
+        loop {
+            let to_remove: Vec<_> = grid
+                .iter_points()
+                .filter(|p| *p.value == Tile::Roll)
+                .filter(|p| rolls_around(&grid, &p.coord) < 4)
+                .map(|p| p.coord)
+                .collect();
+            if to_remove.is_empty() {
+                return counter;
+            }
             counter += to_remove.len();
             for coord in to_remove {
-                *grid.get_val_mut(&coord) = '.';
+                *grid.get_val_mut(&coord) = Tile::Empty;
             }
         }
-        counter.to_string()
     }
-}
\ No newline at end of file
+
+    /// Run with `cargo test --release bench_neighbours -- --ignored --nocapture`
+    #[test]
+    #[ignore]
+    fn bench_neighbours() {
+        let input = PrintingDepartment::generate(&mut Rng::new(4), 300);
+        let department = PrintingDepartment::new(&input);
+
+        // `adjacent_coords` allocates a `Vec` for every cell, `neighbours`
+        // iterates over the directions
+        let start = Instant::now();
+        let allocating = remove_rounds(department.grid.clone(), |grid, coord| {
+            grid.adjacent_coords(coord).iter().filter(|c| *grid.get_val(c) == Tile::Roll).count()
+        });
+        let allocating_time = start.elapsed();
+        let start = Instant::now();
+        let iterating = remove_rounds(department.grid.clone(), |grid, coord| {
+            grid.neighbours(*coord, Neighbourhood::Adjacent).filter(|c| *grid.get_val(c) == Tile::Roll).count()
+        });
+        let iterating_time = start.elapsed();
+
+        assert_eq!(allocating, iterating);
+        assert_eq!(allocating.to_string(), department.part_02());
+        println!("adjacent_coords: {:?}, neighbours: {:?}", allocating_time, iterating_time);
+    }
+}
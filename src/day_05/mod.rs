@@ -1,3 +1,5 @@
+use crate::utils::random::Rng;
+
 pub struct Cafeteria {
     fresh_ingredients: Vec<(usize, usize)>,
     available_ingredients: Vec<usize>
@@ -8,7 +10,10 @@ impl crate::Advent for Cafeteria {
         where 
             Self: Sized {
         
-        let (fresh_str, available_str) = data.split_once("\r\n\r\n").unwrap();
+        let (fresh_str, available_str) = data
+            .split_once("\r\n\r\n")
+            .or_else(|| data.split_once("\n\n"))
+            .unwrap();
 
         let fresh: Vec<(usize, usize)> = fresh_str.lines().map(|l| {
             let (lhs, rhs) = l.split_once("-").unwrap();
@@ -24,6 +29,16 @@ impl crate::Advent for Cafeteria {
         Self { fresh_ingredients: fresh, available_ingredients: available }
     }
 
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        let max_id = 1_000_000_000_000;
+        let fresh: Vec<String> = (0..size).map(|_| {
+            let start = rng.range(1..=max_id);
+            format!("{}-{}", start, start + rng.range(0..=max_id / 100))
+        }).collect();
+        let available: Vec<String> = (0..size).map(|_| rng.range(1..=max_id).to_string()).collect();
+        format!("{}\n\n{}", fresh.join("\n"), available.join("\n"))
+    }
+
     fn part_01(&self) -> String {
         let mut counter = 0;
         'ingredient_loop: for ingredient in &self.available_ingredients {
@@ -63,4 +78,35 @@ impl crate::Advent for Cafeteria {
         joined_ranges.push(current);
         joined_ranges.iter().map(|r| (r.0..=r.1).count()).sum::<usize>().to_string()
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::Advent;
+    use crate::utils::differential::{Differential, shrink_usize, shrink_vec};
+    use crate::utils::random::Rng;
+
+    #[test]
+    fn test_fresh_count_differential() {
+        let differential = Differential::new(
+            |rng: &mut Rng| {
+                (0..rng.range(1..=8)).map(|_| {
+                    let start = rng.range(0..=40);
+                    (start, start + rng.range(0..=10))
+                }).collect::<Vec<_>>()
+            },
+            |ranges: &Vec<(usize, usize)>| {
+                shrink_vec(ranges, |(start, end)| {
+                    shrink_usize(&(end - start)).into_iter().map(|len| (*start, start + len)).collect()
+                }).into_iter().filter(|r| !r.is_empty()).collect()
+            }
+        );
+        differential.check(
+            |ranges| ranges.iter().flat_map(|r| r.0..=r.1).collect::<HashSet<usize>>().len().to_string(),
+            |ranges| Cafeteria { fresh_ingredients: ranges.clone(), available_ingredients: vec![] }.part_02()
+        );
+    }
+}
@@ -1,3 +1,5 @@
+use crate::utils::random::Rng;
+
 pub struct TrashCompactor {
     numbers: Vec<Vec<usize>>,
     signs: Vec<char>,
@@ -43,6 +45,27 @@ impl crate::Advent for TrashCompactor {
         Self { numbers, signs, cephalopod_numbers }
     }
 
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        let mut lines = vec![String::new(); 5];
+        for i in 0..size {
+            let numbers: Vec<String> = (0..4).map(|_| rng.range(1..=9999).to_string()).collect();
+            let width = numbers.iter().map(|n| n.len()).max().unwrap();
+            let right_aligned = rng.chance(1, 2);
+            let separator = if i == 0 { "" } else { " " };
+            for (line, number) in lines.iter_mut().zip(&numbers) {
+                line.push_str(separator);
+                if right_aligned {
+                    line.push_str(&format!("{:>width$}", number, width = width));
+                } else {
+                    line.push_str(&format!("{:<width$}", number, width = width));
+                }
+            }
+            let sign = if rng.chance(1, 2) { '+' } else { '*' };
+            lines[4].push_str(&format!("{}{:<width$}", separator, sign, width = width));
+        }
+        lines.join("\n")
+    }
+
     fn part_01(&self) -> String {
         let mut results: Vec<usize> = vec![];
         for (i, sign) in self.signs.iter().enumerate() {
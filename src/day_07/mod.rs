@@ -1,6 +1,33 @@
 use std::collections::{HashMap, HashSet};
 
-use crate::utils::coordinate_system::cartesian::Coord;
+use crate::utils::coordinate_system::cartesian::{Coord, FromCell, Grid, ToCell};
+use crate::utils::random::Rng;
+
+const MAX_SPLITTER_ROWS: usize = 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Tile { Start, Splitter, Empty }
+
+impl FromCell for Tile {
+    fn from_cell(c: char) -> Option<Self> {
+        match c {
+            'S' => Some(Tile::Start),
+            '^' => Some(Tile::Splitter),
+            '.' => Some(Tile::Empty),
+            _ => None
+        }
+    }
+}
+
+impl ToCell for Tile {
+    fn to_cell(&self) -> char {
+        match self {
+            Tile::Start => 'S',
+            Tile::Splitter => '^',
+            Tile::Empty => '.',
+        }
+    }
+}
 
 pub struct Laboratories {
     splitters: Vec<Coord<usize>>,
@@ -13,14 +40,30 @@ impl crate::Advent for Laboratories {
         where 
             Self: Sized {
 
-        let start = data.lines().next().unwrap().chars().enumerate().filter(|(_, c)| *c == 'S').map(|(i, _)| Coord::new(i, 0)).next().unwrap();
-        let splitters: Vec<Coord<usize>> = data.lines().enumerate().map(|(y, l)| {
-            l.chars().enumerate().filter(|(_, c)| *c == '^').map(|(x, _)| {
-                Coord::new(x, y)
-            }).collect::<Vec<_>>()
-        }).flatten().collect();
-        let height = data.lines().count();
-        Self { splitters, start, height }
+        let grid: Grid<Tile> = Grid::parse(data).unwrap();
+        let start = grid.iter_points().find(|p| *p.value == Tile::Start).unwrap().coord;
+        let splitters: Vec<Coord<usize>> = grid.iter_points().filter(|p| *p.value == Tile::Splitter).map(|p| p.coord).collect();
+        Self { splitters, start, height: grid.height }
+    }
+
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        let width = 2 * size + 1;
+        // Every row of splitters can double the timelines, with at most 60
+        // of them the answer of part 2 always fits
+        let spacing = size.div_ceil(MAX_SPLITTER_ROWS);
+        (0..2 * size).map(|y| {
+            let splitter_row = y > 0 && y % 2 == 0 && (y / 2) % spacing == 0;
+            (0..width).map(|x| {
+                let tile = if y == 0 && x == size {
+                    Tile::Start
+                } else if splitter_row && x > 0 && x < width - 1 && rng.chance(1, 3) {
+                    Tile::Splitter
+                } else {
+                    Tile::Empty
+                };
+                tile.to_cell()
+            }).collect::<String>()
+        }).collect::<Vec<_>>().join("\n")
     }
 
     fn part_01(&self) -> String {
@@ -1,3 +1,5 @@
+use crate::utils::random::Rng;
+
 pub struct Playground {
     junkctions: Vec<[isize; 3]>,
     distances: Vec<(isize, usize, usize)>,
@@ -30,6 +32,20 @@ impl crate::Advent for Playground {
         Self { junkctions, distances, to_connect }
     }
 
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        (0..size).map(|_| {
+            format!("{},{},{}", rng.range(0..=99_999), rng.range(0..=99_999), rng.range(0..=99_999))
+        }).collect::<Vec<_>>().join("\n")
+    }
+
+    fn set_parameter(&mut self, name: &str, value: &str) -> anyhow::Result<()> {
+        match name {
+            "to_connect" => self.to_connect = value.parse()?,
+            _ => return Err(anyhow::anyhow!("Unknown parameter `{}`", name)),
+        }
+        Ok(())
+    }
+
     fn part_01(&self) -> String {
         let mut circuts: Vec<Vec<usize>> = vec![];
         let mut counter = 0;
@@ -1,4 +1,7 @@
-use crate::utils::coordinate_system::cartesian::{Coord, Axis};
+use std::cmp;
+
+use crate::utils::coordinate_system::cartesian::{Coord, Axis, Compressed, Neighbourhood};
+use crate::utils::random::Rng;
 
 pub struct MovieTheater {
     red_tiles: Vec<Coord<usize>>
@@ -16,6 +19,23 @@ impl crate::Advent for MovieTheater {
         Self { red_tiles }
     }
 
+    /// Generates a skyline shaped polygon, every column adds two corners.
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        let columns = cmp::max(1, size.saturating_sub(2) / 2);
+        let max_gap = cmp::max(1, 100_000 / (columns + 1));
+        let start = rng.range(1..=max_gap);
+        let mut height = 1;
+        let columns: Vec<(usize, usize)> = (0..columns).map(|_| {
+            let mut new_height = rng.range(2..=100_000);
+            while new_height == height {
+                new_height = rng.range(2..=100_000);
+            }
+            height = new_height;
+            (height, rng.range(1..=max_gap))
+        }).collect();
+        Self::skyline(start, &columns).iter().map(|c| format!("{},{}", c.x, c.y)).collect::<Vec<_>>().join("\n")
+    }
+
     fn part_01(&self) -> String {
         let mut max_area: usize = 0;
         for (i, lhs) in self.red_tiles.iter().enumerate() {
@@ -30,24 +50,81 @@ impl crate::Advent for MovieTheater {
         max_area.to_string()
     }
 
+    /// Fills the polygon on a coordinate compressed grid, then every
+    /// rectangle is inside when it's area is the number of inside tiles.
     fn part_02(&self) -> String {
+        let compressed = Compressed::new(&self.red_tiles);
+        let mut edges = compressed.grid(false);
+        let closing = [*self.red_tiles.last().unwrap(), self.red_tiles[0]];
+        for pair in self.red_tiles.windows(2).chain([&closing[..]]) {
+            let (a, b) = compressed.rect(&pair[0], &pair[1]).unwrap();
+            for y in a.y..=b.y {
+                for x in a.x..=b.x {
+                    *edges.get_val_mut(&Coord::new(x, y)) = true;
+                }
+            }
+        }
+
+        // Everything the outside can reach around the edges is not inside
+        let (width, height) = (edges.width, edges.height);
+        edges.pad(1, 1, 1, 1, false);
+        let mut inside = edges.clone();
+        inside.map.fill(true);
+        for coord in edges.flood_fill(Coord::new(0, 0), Neighbourhood::Touching, |_, to| !*to.value) {
+            *inside.get_val_mut(&coord) = false;
+        }
+        inside.crop(Coord::new(1, 1), width, height);
+
+        let table = compressed.summed_area(&inside, |v| *v as usize);
+        let mut max_area = 0;
+        for (i, lhs) in self.red_tiles.iter().enumerate() {
+            for rhs in self.red_tiles.iter().skip(i + 1) {
+                let area = Self::area(lhs, rhs);
+                if area <= max_area {
+                    continue;
+                }
+                let (a, b) = compressed.rect(lhs, rhs).unwrap();
+                if table.sum(&a, &b) == area {
+                    max_area = area;
+                }
+            }
+        }
+        max_area.to_string()
+    }
+
+    fn variants(&self) -> Vec<crate::Variant<'_>> {
+        vec![
+            crate::Variant::new("crossings", crate::Part::Two, || self.edge_crossing()),
+        ]
+    }
+}
+
+impl MovieTheater {
+    /// Checks every rectangle against the edges of the loop: no red tile
+    /// and no edge may be strictly inside it, then it's centre decides if
+    /// it's inside. Treats the loop as a continuous polygon, so a gap of a
+    /// single tile between two edges counts as outside.
+    fn edge_crossing(&self) -> String {
         let mut path = self.red_tiles.clone();
-        path.push(self.red_tiles.iter().next().unwrap().clone());
+        path.push(self.red_tiles[0]);
+        // Doubled so the centre of every rectangle is on a whole coordinate
+        let doubled: Vec<Coord<usize>> = path.iter().map(|c| Coord::new(2 * c.x, 2 * c.y)).collect();
         let mut max_area = 0;
         for (i, lhs) in self.red_tiles.iter().enumerate() {
-            'search: for (j, rhs) in self.red_tiles.iter().enumerate().skip(i + 1) {
+            'search: for rhs in self.red_tiles.iter().skip(i + 1) {
                 if lhs.x == rhs.x || lhs.y == rhs.y {
                     continue;
                 }
+                let area = Self::area(lhs, rhs);
+                if area <= max_area {
+                    continue;
+                }
                 let (min_x, max_x) = Self::minmax(lhs.x, rhs.x);
                 let (min_y, max_y) = Self::minmax(lhs.y, rhs.y);
-                            
-                for (z, c) in self.red_tiles.iter().enumerate() {
-                    if z == i || z == j {
-                        continue;
-                    }
+
+                for c in self.red_tiles.iter() {
                     let between_x = c.x > min_x && c.x < max_x;
-                    let between_y = c.y > min_y && c.y < min_y;
+                    let between_y = c.y > min_y && c.y < max_y;
                     if between_x && between_y {
                         continue 'search;
                     }
@@ -56,104 +133,72 @@ impl crate::Advent for MovieTheater {
                 for part in path.windows(2) {
                     let p1 = part[0];
                     let p2 = part[1];
-                    if p1 == *lhs || p2 == *lhs || p1 == *rhs || p2 == *rhs {
-                        continue;
-                    }
-
                     let (same_axis, diff_axis) = if p1.x == p2.x {
                         (Axis::X, Axis::Y)
                     } else {
                         assert_eq!(p1.y, p2.y);
                         (Axis::Y, Axis::X)
                     };
-                                        
+
                     let other_val = *p1.get(&same_axis);
-                    
-                    let in_between1 = match same_axis {
-                        Axis::X => {
-                            min_x <= other_val && other_val <= max_x 
-                        },
-                        Axis::Y => {
-                            min_y <= other_val && other_val <= max_y
-                        }
+                    let in_between = match same_axis {
+                        Axis::X => min_x < other_val && other_val < max_x,
+                        Axis::Y => min_y < other_val && other_val < max_y
                     };
-
-                    if !in_between1 {
+                    if !in_between {
                         continue;
                     }
 
                     let (minp, maxp) = Self::minmax(*p1.get(&diff_axis), *p2.get(&diff_axis));
-                    
                     let crosses = match same_axis {
-                        Axis::X => {
-                            minp < max_y && maxp > min_y
-                        },
-                        Axis::Y => {
-                            minp < max_y && maxp > min_y
-                        }
+                        Axis::X => minp < max_y && maxp > min_y,
+                        Axis::Y => minp < max_x && maxp > min_x
                     };
                     if crosses {
                         continue 'search;
                     }
                 }
 
-                let lhs_corner = Coord::new(lhs.x, rhs.y);
-                let rhs_corner = Coord::new(rhs.x, lhs.y);
-                if !(Self::coord_in_shape(&lhs_corner, &path) && Self::coord_in_shape(&rhs_corner, &path)) {
-                    continue 'search;
-                }
-
-                let area = Self::area(lhs, rhs);
-                if area > max_area {
+                let centre = Coord::new(min_x + max_x, min_y + max_y);
+                if Self::coord_in_shape(&centre, &doubled) {
                     max_area = area;
                 }
-            }            
+            }
         }
         max_area.to_string()
     }
-}
 
-impl MovieTheater {
-    pub fn intersects_with_section(start: &Coord<usize>, lhs: Coord<usize>, rhs: Coord<usize>) -> bool{
-        if lhs.x == rhs.x {
-            // Straight line
-            if start.x != lhs.x {
-                return false;
-            }
-            if lhs.y >= start.y || rhs.y >= start.y {
-                return true;
-            } else {
-                return false;
-            }
-        }
-        assert_eq!(lhs.y, rhs.y, "Failed {} - {}", lhs, rhs);
-
-        if lhs.y <= start.y {
+    /// Whether the ray from `start` towards growing x crosses the section.
+    /// Only vertical sections count, including their lower end but not
+    /// their upper one.
+    pub fn intersects_with_section(start: &Coord<usize>, lhs: Coord<usize>, rhs: Coord<usize>) -> bool {
+        if lhs.x != rhs.x || lhs.x <= start.x {
             return false;
         }
-
-        let (min_x, max_x) = MovieTheater::minmax(lhs.x, rhs.x);
-        if (min_x - 1..=max_x).contains(&start.x) {
-            return true;
-        }
-        false
+        let (min_y, max_y) = MovieTheater::minmax(lhs.y, rhs.y);
+        min_y <= start.y && start.y < max_y
     }
 
-    pub fn coord_in_shape(c: &Coord<usize>, path: &Vec<Coord<usize>>) -> bool {
-        let mut intersect_count = 0;
-        for window in path.windows(2) {
-            let lhs = window[0];
-            let rhs = window[1];
-            if MovieTheater::intersects_with_section(c, lhs, rhs) {
-                intersect_count += 1;
-            }
-        }
+    /// Even-odd rule on the closed `path`, `c` must not be on the path.
+    pub fn coord_in_shape(c: &Coord<usize>, path: &[Coord<usize>]) -> bool {
+        let intersect_count = path.windows(2)
+            .filter(|window| MovieTheater::intersects_with_section(c, window[0], window[1]))
+            .count();
+        intersect_count % 2 == 1
+    }
 
-        if intersect_count % 2 == 0 {
-            false
-        } else {
-            true
+    /// Corners of a skyline starting at `start`, every column is a
+    /// `(height, width)` pair.
+    fn skyline(start: usize, columns: &[(usize, usize)]) -> Vec<Coord<usize>> {
+        let mut x = start;
+        let mut corners = vec![Coord::new(x, 1)];
+        for (height, width) in columns {
+            corners.push(Coord::new(x, *height));
+            x += width;
+            corners.push(Coord::new(x, *height));
         }
+        corners.push(Coord::new(x, 1));
+        corners
     }
 
     pub fn minmax(lhs: usize, rhs: usize) -> (usize, usize) {
@@ -169,4 +214,75 @@ impl MovieTheater {
         let y = Self::udiff(lhs.y, rhs.y) + 1;
         x * y
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Advent;
+    use crate::utils::differential::{Differential, shrink_usize, shrink_vec};
+
+    /// Paints the edges tile by tile, fills the outside from the border
+    /// and checks every tile of every rectangle.
+    fn brute_force(corners: &[Coord<usize>]) -> usize {
+        let width = corners.iter().map(|c| c.x).max().unwrap() + 2;
+        let height = corners.iter().map(|c| c.y).max().unwrap() + 2;
+        let mut edge = vec![vec![false; width]; height];
+        for (i, a) in corners.iter().enumerate() {
+            let b = corners[(i + 1) % corners.len()];
+            for row in edge.iter_mut().take(a.y.max(b.y) + 1).skip(a.y.min(b.y)) {
+                row[a.x.min(b.x)..=a.x.max(b.x)].fill(true);
+            }
+        }
+        let mut outside = vec![vec![false; width]; height];
+        let mut stack = vec![(0, 0)];
+        while let Some((x, y)) = stack.pop() {
+            if x >= width || y >= height || edge[y][x] || outside[y][x] {
+                continue;
+            }
+            outside[y][x] = true;
+            stack.extend([(x + 1, y), (x.wrapping_sub(1), y), (x, y + 1), (x, y.wrapping_sub(1))]);
+        }
+        let mut max_area = 0;
+        for a in corners {
+            for b in corners {
+                let inside = (a.y.min(b.y)..=a.y.max(b.y))
+                    .all(|y| (a.x.min(b.x)..=a.x.max(b.x)).all(|x| !outside[y][x]));
+                if inside {
+                    max_area = max_area.max(MovieTheater::area(a, b));
+                }
+            }
+        }
+        max_area
+    }
+
+    /// Compares the solvers on random skylines with columns at least
+    /// `min_width` wide.
+    fn check_skylines(min_width: usize, reference: impl Fn(&MovieTheater) -> String, solution: impl Fn(&MovieTheater) -> String) {
+        let differential = Differential::new(
+            |rng: &mut Rng| (0..rng.range(1..=6)).map(|_| (rng.range(2..=12), rng.range(min_width..=4))).collect::<Vec<_>>(),
+            |columns: &Vec<(usize, usize)>| {
+                shrink_vec(columns, |(height, width)| {
+                    let lower = shrink_usize(height).into_iter().filter(|h| *h >= 2).map(|h| (h, *width));
+                    let narrower = shrink_usize(width).into_iter().filter(|w| *w >= min_width).map(|w| (*height, w));
+                    lower.chain(narrower).collect()
+                }).into_iter().filter(|c| !c.is_empty()).collect()
+            }
+        );
+        differential.check(
+            |columns| reference(&MovieTheater { red_tiles: MovieTheater::skyline(1, columns) }),
+            |columns| solution(&MovieTheater { red_tiles: MovieTheater::skyline(1, columns) })
+        );
+    }
+
+    #[test]
+    fn test_inside_rect_differential() {
+        check_skylines(1, |theater| brute_force(&theater.red_tiles).to_string(), |theater| theater.part_02());
+    }
+
+    #[test]
+    fn test_crossings_differential() {
+        // Gaps of a single tile are only outside for the continuous polygon
+        check_skylines(2, |theater| theater.part_02(), |theater| theater.edge_crossing());
+    }
+}
@@ -1,3 +1,5 @@
+use crate::utils::random::Rng;
+
 
 pub struct Factory {
     machines: Vec<Machine>
@@ -45,6 +47,44 @@ impl crate::Advent for Factory {
         Self { machines }
     }
 
+    /// Lights and joltages are made from random button presses, so every
+    /// machine is solvable.
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        (0..size).map(|_| {
+            let lights_len = rng.range(3..=10);
+            let buttons: Vec<Vec<usize>> = (0..rng.range(3..=lights_len + 3)).map(|_| {
+                let mut button: Vec<usize> = (0..lights_len).filter(|_| rng.chance(1, 3)).collect();
+                if button.is_empty() {
+                    button.push(rng.range(0..=lights_len - 1));
+                }
+                button
+            }).collect();
+
+            let mut lights = vec![false; lights_len];
+            while !lights.contains(&true) {
+                for button in buttons.iter().filter(|_| rng.chance(1, 2)) {
+                    for i in button {
+                        lights[*i] = !lights[*i];
+                    }
+                }
+            }
+            let mut joltages = vec![0; lights_len];
+            for button in &buttons {
+                let presses = rng.range(0..=20);
+                for i in button {
+                    joltages[*i] += presses;
+                }
+            }
+
+            let lights: String = lights.iter().map(|on| if *on { '#' } else { '.' }).collect();
+            let buttons: Vec<String> = buttons.iter().map(|b| {
+                format!("({})", b.iter().map(|i| i.to_string()).collect::<Vec<_>>().join(","))
+            }).collect();
+            let joltages: Vec<String> = joltages.iter().map(|j| j.to_string()).collect();
+            format!("[{}] {} {{{}}}", lights, buttons.join(" "), joltages.join(","))
+        }).collect::<Vec<_>>().join("\n")
+    }
+
     fn part_01(&self) -> String {
 
         let mut total = 0;
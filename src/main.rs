@@ -1,4 +1,6 @@
 pub mod utils;
+mod cache;
+mod config;
 
 mod day_01;
 mod day_02;
@@ -11,13 +13,18 @@ mod day_08;
 mod day_09;
 mod day_10;
 
-use std::{error::Error, fs};
+use std::{error::Error, fmt, fs, thread};
+use std::sync::mpsc;
+use std::path::{Path, PathBuf};
 use structopt::StructOpt;
-use owo_colors::OwoColorize;
-use owo_colors::colors::{Magenta, Cyan};
+use owo_colors::{OwoColorize, Stream};
+use owo_colors::colors::{Magenta, Cyan, Red};
 use std::time::{Duration, Instant};
-use anyhow::Context;
+use anyhow::{Context, anyhow};
 use humantime::format_duration;
+use utils::random::Rng;
+use cache::{Cache, CACHE_FILE};
+use config::{Color, Config, Format};
 
 fn get_time<T>(f: impl FnOnce() -> T) -> (T, Duration) {
     let start = Instant::now();
@@ -32,6 +39,90 @@ trait Advent {
         Self: Sized;
     fn part_01(&self) -> String;
     fn part_02(&self) -> String;
+
+    /// Random valid puzzle input, `size` is the number of the main puzzle
+    /// elements (lines, ranges, grid side, ...). Used by the `generate`
+    /// subcommand for stress testing.
+    fn generate(rng: &mut Rng, size: usize) -> String
+    where 
+        Self: Sized;
+
+    /// Alternative implementations of the parts. They are run next to
+    /// `part_01` / `part_02` (registered as `default`) when selected with
+    /// `--variant` or `--all-variants`.
+    fn variants(&self) -> Vec<Variant<'_>> {
+        vec![]
+    }
+
+    /// Sets a puzzle parameter from the `[day.N]` table of the config.
+    fn set_parameter(&mut self, name: &str, _value: &str) -> anyhow::Result<()> {
+        Err(anyhow!("Unknown parameter `{}`", name))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Part { One, Two }
+
+impl fmt::Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Part::One => write!(f, "1"),
+            Part::Two => write!(f, "2"),
+        }
+    }
+}
+
+const DEFAULT_VARIANT: &str = "default";
+
+/// Named implementation of a single part of the day.
+struct Variant<'a> {
+    name: &'static str,
+    part: Part,
+    solve: Box<dyn Fn() -> String + 'a>,
+}
+
+impl<'a> Variant<'a> {
+    fn new(name: &'static str, part: Part, solve: impl Fn() -> String + 'a) -> Self {
+        Variant { name, part, solve: Box::new(solve) }
+    }
+}
+
+/// Answer of a single variant of a part.
+struct Answer {
+    variant: String,
+    value: String,
+    /// `None` for answers read from the cache
+    time: Option<Duration>,
+}
+
+/// Answers of the selected variants of every part.
+type Results = Vec<(Part, Vec<Answer>)>;
+
+/// Which variants of the parts get run.
+#[derive(Clone)]
+enum VariantSelection {
+    Default,
+    Named(String),
+    All,
+}
+
+impl VariantSelection {
+    /// Name of the selected variant, `None` when all of them run.
+    fn name(&self) -> Option<&str> {
+        match self {
+            VariantSelection::Default => Some(DEFAULT_VARIANT),
+            VariantSelection::Named(name) => Some(name),
+            VariantSelection::All => None,
+        }
+    }
+
+    /// Whether a named variant was not found and the default one ran instead.
+    fn fell_back(&self, results: &Results) -> bool {
+        match self {
+            VariantSelection::Named(name) => !results.iter().flat_map(|(_, answers)| answers).any(|a| &a.variant == name),
+            _ => false,
+        }
+    }
 }
 
 struct Solution {
@@ -40,41 +131,148 @@ struct Solution {
 }
 
 impl Solution {
-    fn new<Event: Advent + 'static>(content: &str) -> Self {
-        let (event, time) = get_time(|| Event::new(content));
+    fn new<Event: Advent + 'static>(content: &str, parameters: &[(String, String)]) -> anyhow::Result<Self> {
+        let (event, time) = get_time(|| {
+            let mut event = Event::new(content);
+            for (name, value) in parameters {
+                event.set_parameter(name, value)
+                    .with_context(|| format!("Invalid parameter `{}` = `{}`", name, value))?;
+            }
+            anyhow::Ok(event)
+        });
 
-        Solution {
-            event: Box::new(event),
+        Ok(Solution {
+            event: Box::new(event?),
             time,
+        })
+    }
+
+    /// Default implementation of the part followed by all of it's variants.
+    fn part_variants(&self, part: Part) -> Vec<Variant<'_>> {
+        let default = match part {
+            Part::One => Variant::new(DEFAULT_VARIANT, part, || self.event.part_01()),
+            Part::Two => Variant::new(DEFAULT_VARIANT, part, || self.event.part_02()),
+        };
+        let mut variants = vec![default];
+        variants.extend(self.event.variants().into_iter().filter(|v| v.part == part));
+        variants
+    }
+
+    /// Runs the selected variants of both parts.
+    fn run(&self, selection: &VariantSelection) -> Results {
+        let mut results = vec![];
+        for part in [Part::One, Part::Two] {
+            let mut variants = self.part_variants(part);
+            match selection {
+                VariantSelection::Default => variants.truncate(1),
+                VariantSelection::Named(name) => {
+                    // Days without the variant fall back to the default one
+                    if let Some(i) = variants.iter().position(|v| v.name == name) {
+                        variants.swap(0, i);
+                    }
+                    variants.truncate(1);
+                },
+                VariantSelection::All => {}
+            }
+            let answers = variants.iter().map(|v| {
+                let (value, time) = get_time(|| (v.solve)());
+                Answer { variant: v.name.to_owned(), value, time: Some(time) }
+            }).collect();
+            results.push((part, answers));
         }
+        results
     }
+}
 
-    fn get_result(&self, day: u32) -> Duration {
-        let (part1, time1) = get_time(|| self.event.part_01());
-        let (part2, time2) = get_time(|| self.event.part_02());
-        let time_sum = self.time + time1 + time2;
+fn cyan(value: impl fmt::Display) -> String {
+    value.if_supports_color(Stream::Stdout, |v| v.fg::<Cyan>()).to_string()
+}
+
+fn magenta(value: impl fmt::Display) -> String {
+    value.if_supports_color(Stream::Stdout, |v| v.fg::<Magenta>()).to_string()
+}
+
+fn red(value: impl fmt::Display) -> String {
+    value.if_supports_color(Stream::Stdout, |v| v.fg::<Red>()).to_string()
+}
+
+/// Prints the answers of the day, `parse_time` is `None` when the answers
+/// come from the cache. Returns the time of the solution and the parts
+/// whose variants returned different answers.
+fn print_result(day: u32, parse_time: Option<Duration>, results: &[(Part, Vec<Answer>)], config: &Config) -> (Duration, Vec<Part>) {
+    let time_sum = parse_time.unwrap_or_default()
+        + results.iter().filter_map(|(_, answers)| answers[0].time).sum::<Duration>();
+    let mismatches: Vec<Part> = results
+        .iter()
+        .filter(|(_, answers)| answers.iter().any(|answer| answer.value != answers[0].value))
+        .map(|(part, _)| *part)
+        .collect();
+
+    if config.format == Format::Compact {
+        for (part, answers) in results {
+            for answer in answers {
+                let time = answer.time.map(|t| t.as_nanos().to_string()).unwrap_or("cached".to_owned());
+                println!("{}\t{}\t{}\t{}\t{}", day, part, answer.variant, answer.value, time);
+            }
+        }
+        return (time_sum, mismatches);
+    }
+
+    let verbose = config.verbosity > 0;
+    let format_time = |time: Option<Duration>| match time {
+        _ if !verbose => String::new(),
+        Some(time) => format!(" in {}", magenta(format_duration(time))),
+        None => format!(" {}", magenta("(cached)")),
+    };
+
+    if verbose {
         println!("--------------------------");
-        println!(
-            "Solution for day {} in {}", 
-            day.fg::<Cyan>(),
-            format_duration(time_sum).fg::<Cyan>()
-        );
-        println!(
-            "Collected data in {}",
-            format_duration(self.time).fg::<Magenta>()    
-        );
-        println!(
-            "Part 1: {} in {}",
-            part1.fg::<Cyan>(),
-            format_duration(time1).fg::<Magenta>()
-        );
-        println!(
-            "Part 2: {} in {}",
-            part2.fg::<Cyan>(),
-            format_duration(time2).fg::<Magenta>()
-        );
-        time_sum
+        match parse_time {
+            Some(parse_time) => {
+                println!(
+                    "Solution for day {} in {}", 
+                    cyan(day),
+                    cyan(format_duration(time_sum))
+                );
+                println!(
+                    "Collected data in {}",
+                    magenta(format_duration(parse_time))
+                );
+            },
+            None => println!("Solution for day {} {}", cyan(day), cyan("(cached)")),
+        }
+    }
+
+    let prefix = if verbose { String::new() } else { format!("Day {} ", day) };
+    for (part, answers) in results {
+        if answers.len() == 1 {
+            let answer = &answers[0];
+            let name = if answer.variant == DEFAULT_VARIANT { String::new() } else { format!(" [{}]", answer.variant) };
+            println!(
+                "{}Part {}{}: {}{}",
+                prefix,
+                part,
+                name,
+                cyan(&answer.value),
+                format_time(answer.time)
+            );
+            continue;
+        }
+
+        println!("{}Part {}:", prefix, part);
+        for answer in answers {
+            println!(
+                "  {:<12} {}{}",
+                answer.variant,
+                cyan(&answer.value),
+                format_time(answer.time)
+            );
+        }
+        if mismatches.contains(part) {
+            println!("  {}", red("Variants disagree!"));
+        }
     }
+    (time_sum, mismatches)
 }
 
 
@@ -85,50 +283,343 @@ struct Cli {
     #[structopt(short, long, help = "Uses example file provided by AOC")]
     example: bool,
 
+    #[structopt(long, help = "Runs the named variant of the parts instead of the default one")]
+    variant: Option<String>,
+
+    #[structopt(long, conflicts_with = "variant", help = "Runs and cross-checks all variants of the parts")]
+    all_variants: bool,
+
+    #[structopt(short, long, parse(from_os_str), conflicts_with = "example", requires = "day", help = "Reads the input of the day from the file")]
+    input: Option<PathBuf>,
+
+    #[structopt(long, conflicts_with = "all-variants", help = "Reuses the answers of previous runs with the same input and build")]
+    cached: bool,
+
+    #[structopt(long, help = "Ignores the cache, overrides --cached")]
+    no_cache: bool,
+
+    #[structopt(long, parse(from_os_str), help = "Config file to use instead of aoc.toml")]
+    config: Option<PathBuf>,
+
+    #[structopt(long, help = "Prints the effective configuration and exits")]
+    print_config: bool,
+
+    #[structopt(long, parse(from_os_str), help = "Directory with the day_XX input folders")]
+    input_dir: Option<PathBuf>,
+
+    #[structopt(long, help = "Output format: pretty or compact")]
+    format: Option<Format>,
+
+    #[structopt(long, help = "When to color the output: auto, always or never")]
+    color: Option<Color>,
+
+    #[structopt(long, parse(try_from_str = humantime::parse_duration), help = "Time limit of a single day (e.g. 10s), a timed out solver keeps running in the background")]
+    timeout: Option<Duration>,
+
+    #[structopt(short, long, parse(from_occurrences), help = "Prints the inputs as well")]
+    verbose: u8,
+
+    #[structopt(short, long, conflicts_with = "verbose", help = "Prints only the answers")]
+    quiet: bool,
+
+    #[structopt(subcommand)]
+    command: Option<Command>,
+}
+
+impl Cli {
+    /// Flags override the values of the config file.
+    fn apply(&self, config: &mut Config) {
+        if let Some(day) = self.day {
+            config.days = vec![day];
+        }
+        if let Some(input_dir) = &self.input_dir {
+            config.input_dir = input_dir.clone();
+        }
+        if let Some(format) = self.format {
+            config.format = format;
+        }
+        if let Some(color) = self.color {
+            config.color = color;
+        }
+        if self.timeout.is_some() {
+            config.timeout = self.timeout;
+        }
+        if self.quiet {
+            config.verbosity = 0;
+        } else if self.verbose > 0 {
+            config.verbosity = (1 + self.verbose).min(2);
+        }
+        config.cache = (config.cache || self.cached) && !self.no_cache;
+    }
+}
+
+#[derive(StructOpt)]
+enum Command {
+    #[structopt(about = "Writes a random valid input for the day")]
+    Generate {
+        day: u32,
+
+        #[structopt(long, default_value = "100", help = "Number of the main puzzle elements")]
+        size: usize,
+
+        #[structopt(long, default_value = "0")]
+        seed: u64,
+
+        #[structopt(short, long, parse(from_os_str), help = "Writes to the file instead of stdout")]
+        output: Option<PathBuf>,
+    }
+}
+
+fn solve(day: u32, content: &str, parameters: &[(String, String)]) -> anyhow::Result<Solution> {
+    match day {
+        1 => Solution::new::<day_01::SecretEntrance>(content, parameters),
+        2 => Solution::new::<day_02::GiftShop>(content, parameters),
+        3 => Solution::new::<day_03::Lobby>(content, parameters),
+        4 => Solution::new::<day_04::PrintingDepartment>(content, parameters),
+        5 => Solution::new::<day_05::Cafeteria>(content, parameters),
+        6 => Solution::new::<day_06::TrashCompactor>(content, parameters),
+        7 => Solution::new::<day_07::Laboratories>(content, parameters),
+        8 => Solution::new::<day_08::Playground>(content, parameters),
+        9 => Solution::new::<day_09::MovieTheater>(content, parameters),
+        10 => Solution::new::<day_10::Factory>(content, parameters),
+        _ => Err(anyhow!("There is no day {}", day)),
+    }
+}
+
+fn generate(day: u32, rng: &mut Rng, size: usize) -> anyhow::Result<String> {
+    let content = match day {
+        1 => day_01::SecretEntrance::generate(rng, size),
+        2 => day_02::GiftShop::generate(rng, size),
+        3 => day_03::Lobby::generate(rng, size),
+        4 => day_04::PrintingDepartment::generate(rng, size),
+        5 => day_05::Cafeteria::generate(rng, size),
+        6 => day_06::TrashCompactor::generate(rng, size),
+        7 => day_07::Laboratories::generate(rng, size),
+        8 => day_08::Playground::generate(rng, size),
+        9 => day_09::MovieTheater::generate(rng, size),
+        10 => day_10::Factory::generate(rng, size),
+        _ => return Err(anyhow!("There is no day {}", day)),
+    };
+    Ok(content)
+}
+
+/// Solves the day on it's own thread, so it can be abandoned when it runs
+/// longer than the timeout. Returns `None` on timeout. The thread can't be
+/// cancelled, it keeps running until the process exits.
+fn solve_with_timeout(
+    day: u32,
+    content: String,
+    parameters: Vec<(String, String)>,
+    selection: VariantSelection,
+    timeout: Option<Duration>
+) -> anyhow::Result<Option<(Duration, Results)>> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let result = solve(day, &content, &parameters).map(|solution| {
+            (solution.time, solution.run(&selection))
+        });
+        sender.send(result).ok();
+    });
+    let result = match timeout {
+        Some(timeout) => match receiver.recv_timeout(timeout) {
+            Err(mpsc::RecvTimeoutError::Timeout) => return Ok(None),
+            result => result.ok(),
+        },
+        None => receiver.recv().ok(),
+    };
+    match result {
+        Some(result) => result.map(Some),
+        None => Err(anyhow!("Solution for day {} panicked", day)),
+    }
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
     let args = Cli::from_args();
 
+    if let Some(Command::Generate { day, size, seed, output }) = args.command {
+        let content = generate(day, &mut Rng::new(seed), size)?;
+        match output {
+            Some(path) => fs::write(&path, content + "\n")
+                .with_context(|| format!("Could not write {}", path.display()))?,
+            None => println!("{}", content),
+        }
+        return Ok(());
+    }
+
+    let mut config = Config::load(args.config.as_deref())?;
+    args.apply(&mut config);
+    if args.print_config {
+        print!("{}", toml::to_string_pretty(&config)?);
+        return Ok(());
+    }
+    match config.color {
+        Color::Always => owo_colors::set_override(true),
+        Color::Never => owo_colors::set_override(false),
+        Color::Auto => {}
+    }
+
     let main_file = if args.example { "example" } else { "input" };
     
-    let days = if let Some(day) = args.day {
-        day..=day
+    let days = if config.days.is_empty() {
+        (1u32..=10u32).collect()
     } else {
-        1u32..=10u32
+        config.days.clone()
+    };
+    let selection = match (args.variant, args.all_variants) {
+        (_, true) => VariantSelection::All,
+        (Some(name), false) => VariantSelection::Named(name),
+        (None, false) => VariantSelection::Default,
+    };
+    let mut cache = if config.cache && selection.name().is_some() {
+        Some(Cache::load(Path::new(CACHE_FILE)).context("Could not load the cache")?)
+    } else {
+        None
     };
     let mut duration = Duration::new(0, 0);
+    let mut mismatches: Vec<(u32, Part)> = vec![];
+    let mut timed_out: Vec<u32> = vec![];
+    let mut fallbacks: Vec<u32> = vec![];
+    let day_count = days.len();
 
     for day in days {
-        let filename = format!("src/day_{:02}/{}.txt", day, main_file);
-
-        let mut content: &str = &fs::read_to_string(filename)
-            .with_context(|| format!("Could not read {} file for day {}", main_file, day))?;
-        content = content.trim_end();
-
-        let solution = match day {
-            1 => Solution::new::<day_01::SecretEntrance>(content),
-            2 => Solution::new::<day_02::GiftShop>(content),
-            3 => Solution::new::<day_03::Lobby>(content),
-            4 => Solution::new::<day_04::PrintingDepartment>(content),
-            5 => Solution::new::<day_05::Cafeteria>(content),
-            6 => Solution::new::<day_06::TrashCompactor>(content),
-            7 => Solution::new::<day_07::Laboratories>(content),
-            8 => Solution::new::<day_08::Playground>(content),
-            9 => Solution::new::<day_09::MovieTheater>(content),
-            10 => Solution::new::<day_10::Factory>(content),
-            _ => unreachable!(),
+        let filename = match &args.input {
+            Some(path) => path.clone(),
+            None => config.input_dir.join(format!("day_{:02}", day)).join(format!("{}.txt", main_file)),
+        };
+
+        let content = fs::read_to_string(&filename)
+            .with_context(|| format!("Could not read {} for day {}", filename.display(), day))?;
+        let content = content.trim_end();
+        if config.verbosity > 1 {
+            println!("Input of day {}: {} ({} lines)", day, filename.display(), content.lines().count());
+        }
+
+        let parameters = config.parameters(day);
+        let input_hash = cache::hash_input(content, &parameters);
+        let cached: Option<Results> = match (&cache, selection.name()) {
+            (Some(cache), Some(name)) => [Part::One, Part::Two].into_iter().map(|part| {
+                cache.get(day, part, name, input_hash).map(|(variant, value)| {
+                    (part, vec![Answer { variant: variant.clone(), value: value.clone(), time: None }])
+                })
+            }).collect(),
+            _ => None,
+        };
+
+        if let Some(results) = &cached && selection.fell_back(results) {
+            fallbacks.push(day);
+        }
+        let (time, parts) = match cached {
+            Some(results) => print_result(day, None, &results, &config),
+            None => {
+                let solved = solve_with_timeout(
+                    day, content.to_owned(), parameters, selection.clone(), config.timeout
+                )?;
+                let Some((parse_time, results)) = solved else {
+                    println!("{}", red(format!("Day {} timed out", day)));
+                    timed_out.push(day);
+                    continue;
+                };
+                if selection.fell_back(&results) {
+                    fallbacks.push(day);
+                }
+                if let (Some(cache), Some(name)) = (&mut cache, selection.name()) {
+                    for (part, answers) in &results {
+                        cache.insert(day, *part, name, input_hash, &answers[0].variant, &answers[0].value);
+                    }
+                }
+                let printed = print_result(day, Some(parse_time), &results, &config);
+                if let Some(abandoned) = timed_out.first() && config.format == Format::Pretty && config.verbosity > 0 {
+                    println!("{}", red(format!("Times may be skewed, day {} kept running in the background", abandoned)));
+                }
+                printed
+            }
         };
+        duration += time;
+        mismatches.extend(parts.into_iter().map(|part| (day, part)));
+    }
 
-        duration += solution.get_result(day);        
+    if config.format == Format::Pretty && config.verbosity > 0 {
+        println!("--------------------------");
+        println!(
+            "Duration sum: {}",
+            magenta(format_duration(duration))
+        );
+        println!("--------------------------");
     }
 
-    println!("--------------------------");
-    println!(
-        "Duration sum: {}",
-        format_duration(duration).fg::<Magenta>()
-    );
-    println!("--------------------------");
+    if let Some(cache) = &cache {
+        cache.save().context("Could not save the cache")?;
+    }
 
+    if let VariantSelection::Named(name) = &selection {
+        if !fallbacks.is_empty() && fallbacks.len() == day_count - timed_out.len() {
+            return Err(anyhow!("No selected day has a variant named {}", name).into());
+        }
+        for day in &fallbacks {
+            println!("{}", red(format!("Day {} has no variant named {}, the default one ran", day, name)));
+        }
+    }
+    if !timed_out.is_empty() {
+        let days: Vec<String> = timed_out.iter().map(|day| day.to_string()).collect();
+        return Err(anyhow!("Timed out days: {}", days.join(", ")).into());
+    }
+    if !mismatches.is_empty() {
+        let parts: Vec<String> = mismatches.iter().map(|(day, part)| format!("day {} part {}", day, part)).collect();
+        return Err(anyhow!("Variants disagree for {}", parts.join(", ")).into());
+    }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generated_inputs_are_solvable() {
+        for day in 1..=10 {
+            for seed in 0..3 {
+                let content = generate(day, &mut Rng::new(seed), 5).unwrap();
+                let solution = solve(day, &content, &[]).unwrap();
+                solution.event.part_01();
+                solution.event.part_02();
+            }
+        }
+    }
+
+    #[test]
+    fn test_large_day_07_fits() {
+        let content = generate(7, &mut Rng::new(1), 500).unwrap();
+        let splitter_rows = content.lines().filter(|l| l.contains('^')).count();
+        assert!(splitter_rows < 64, "{} rows of splitters", splitter_rows);
+        solve(7, &content, &[]).unwrap().event.part_02();
+    }
+
+    #[test]
+    fn test_generate_is_seeded() {
+        let lhs = generate(4, &mut Rng::new(7), 10).unwrap();
+        let rhs = generate(4, &mut Rng::new(7), 10).unwrap();
+        assert_eq!(lhs, rhs);
+        assert_eq!(lhs.lines().count(), 10);
+        assert!(generate(11, &mut Rng::new(7), 10).is_err());
+    }
+
+    #[test]
+    fn test_unknown_variant_falls_back() {
+        let content = generate(4, &mut Rng::new(1), 5).unwrap();
+        let solution = solve(4, &content, &[]).unwrap();
+        let named = VariantSelection::Named("bit_grid".to_string());
+        assert!(!named.fell_back(&solution.run(&named)));
+        let typo = VariantSelection::Named("bitgrid".to_string());
+        assert!(typo.fell_back(&solution.run(&typo)));
+        assert!(!VariantSelection::Default.fell_back(&solution.run(&VariantSelection::Default)));
+    }
+
+    #[test]
+    fn test_verbosity_is_capped() {
+        let mut config = Config::default();
+        Cli::from_iter(["aoc", "-vvv"]).apply(&mut config);
+        assert_eq!(config.verbosity, 2);
+        Cli::from_iter(["aoc", "-q"]).apply(&mut config);
+        assert_eq!(config.verbosity, 0);
+    }
+}
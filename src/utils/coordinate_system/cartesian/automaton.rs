@@ -0,0 +1,211 @@
+use std::mem;
+
+use super::coord::Coord;
+use super::grid::Grid;
+use super::neighbourhood::{Neighbourhood, NeighbourOffsets};
+
+/// Neighbours of the cell the rule is evaluated for.
+pub struct Neighbours<'a, V> {
+    grid: &'a Grid<V>,
+    index: usize,
+    offsets: &'a NeighbourOffsets,
+}
+
+impl<'a, V> Neighbours<'a, V> {
+    pub fn coord(&self) -> Coord<usize> {
+        Coord::new(self.index % self.grid.width, self.index / self.grid.width)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &'a V> + '_ {
+        self.offsets.neighbours(self.index).map(|i| &self.grid.map[i])
+    }
+
+    pub fn count_by(&self, predicate: impl Fn(&V) -> bool) -> usize {
+        self.iter().filter(|v| predicate(v)).count()
+    }
+
+    pub fn count(&self, value: &V) -> usize
+    where V: PartialEq
+    {
+        self.count_by(|v| v == value)
+    }
+}
+
+/// Cellular automaton over a grid. Every generation the rule computes the
+/// new value of a cell from it's value and neighbours in the previous
+/// generation, into a second buffer that is swapped in afterwards.
+pub struct Automaton<V, R> {
+    grid: Grid<V>,
+    back: Grid<V>,
+    neighbourhood: Neighbourhood,
+    offsets: NeighbourOffsets,
+    rule: R,
+    generation: usize,
+    changed: Vec<Coord<usize>>,
+    /// Cells to evaluate in the next generation, all of them when `None`
+    active: Option<Vec<Coord<usize>>>,
+    active_frontier: bool,
+    /// Generation in which the cell was last added to `active`
+    queued: Vec<usize>,
+}
+
+impl<V, R> Automaton<V, R>
+where
+    V: Clone + PartialEq,
+    R: Fn(&V, Neighbours<'_, V>) -> V,
+{
+    pub fn new(grid: Grid<V>, neighbourhood: Neighbourhood, rule: R) -> Self {
+        let back = grid.clone();
+        let queued = vec![0; grid.map.len()];
+        let offsets = grid.neighbour_offsets(neighbourhood);
+        Automaton {
+            grid, back, neighbourhood, offsets, rule,
+            generation: 0,
+            changed: vec![],
+            active: None,
+            active_frontier: false,
+            queued,
+        }
+    }
+
+    /// Only re-evaluates the cells that changed in the last generation and
+    /// their neighbours. Valid for rules where a cell can only change when
+    /// it or one of it's neighbours did.
+    pub fn active_frontier(mut self, active_frontier: bool) -> Self {
+        self.active_frontier = active_frontier;
+        self
+    }
+
+    pub fn grid(&self) -> &Grid<V> {
+        &self.grid
+    }
+
+    pub fn into_grid(self) -> Grid<V> {
+        self.grid
+    }
+
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    /// Cells that changed in the last generation.
+    pub fn changed(&self) -> &[Coord<usize>] {
+        &self.changed
+    }
+
+    /// Computes the next generation, returns the number of changed cells.
+    pub fn step(&mut self) -> usize {
+        self.changed.clear();
+        let cells: Vec<Coord<usize>> = match self.active.take() {
+            Some(active) => active,
+            None => self.grid.iter_coords().collect(),
+        };
+        for coord in cells {
+            let index = self.grid.get_index(&coord);
+            let value = &self.grid.map[index];
+            let neighbours = Neighbours { grid: &self.grid, index, offsets: &self.offsets };
+            let next = (self.rule)(value, neighbours);
+            if next != *value {
+                *self.back.get_val_mut(&coord) = next;
+                self.changed.push(coord);
+            }
+        }
+        mem::swap(&mut self.grid, &mut self.back);
+        // Both buffers are the same again, apart from the cells to compute
+        for coord in &self.changed {
+            *self.back.get_val_mut(coord) = self.grid.get_val(coord).clone();
+        }
+        self.generation += 1;
+
+        if self.active_frontier {
+            let mut active = vec![];
+            for coord in &self.changed {
+                let cells = std::iter::once(*coord).chain(self.grid.neighbours(*coord, self.neighbourhood));
+                for cell in cells {
+                    let index = self.grid.get_index(&cell);
+                    if self.queued[index] != self.generation {
+                        self.queued[index] = self.generation;
+                        active.push(cell);
+                    }
+                }
+            }
+            self.active = Some(active);
+        }
+        self.changed.len()
+    }
+
+    /// Runs at most `steps` generations, stopping early on a fixpoint.
+    /// Returns the total number of cell changes.
+    pub fn run(&mut self, steps: usize) -> usize {
+        let mut changes = 0;
+        for _ in 0..steps {
+            match self.step() {
+                0 => break,
+                changed => changes += changed,
+            }
+        }
+        changes
+    }
+
+    /// Runs until a generation changes nothing. Returns the total number
+    /// of cell changes.
+    pub fn run_until_fixpoint(&mut self) -> usize {
+        self.run(usize::MAX)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn life(alive: &bool, neighbours: Neighbours<'_, bool>) -> bool {
+        matches!((alive, neighbours.count(&true)), (true, 2) | (_, 3))
+    }
+
+    fn parse(s: &str) -> Grid<bool> {
+        let grid: Grid<char> = Grid::parse(s).unwrap();
+        Grid { map: grid.map.iter().map(|c| *c == '#').collect(), width: grid.width, height: grid.height }
+    }
+
+    #[test]
+    fn test_blinker() {
+        let mut automaton = Automaton::new(parse(".....\n..#..\n..#..\n..#..\n....."), Neighbourhood::Adjacent, life);
+        assert_eq!(automaton.step(), 4);
+        assert_eq!(automaton.grid().map, parse(".....\n.....\n.###.\n.....\n.....").map);
+        automaton.step();
+        assert_eq!(automaton.grid().map, parse(".....\n..#..\n..#..\n..#..\n.....").map);
+        assert_eq!(automaton.generation(), 2);
+    }
+
+    #[test]
+    fn test_active_frontier() {
+        let start = parse("\
+.#........
+..#.......
+###.......
+..........
+..........
+..........
+..........
+..........");
+        let mut full = Automaton::new(start.clone(), Neighbourhood::Adjacent, life);
+        let mut frontier = Automaton::new(start, Neighbourhood::Adjacent, life).active_frontier(true);
+        for _ in 0..12 {
+            assert_eq!(full.step(), frontier.step());
+            assert_eq!(full.grid().map, frontier.grid().map);
+        }
+    }
+
+    #[test]
+    fn test_fixpoint() {
+        // Cells without a live neighbour die, nothing is born
+        let mut automaton = Automaton::new(
+            parse("##.#\n#...\n...#"),
+            Neighbourhood::Touching,
+            |alive, neighbours| *alive && neighbours.count(&true) >= 1
+        ).active_frontier(true);
+        assert_eq!(automaton.run_until_fixpoint(), 2);
+        assert_eq!(automaton.generation(), 2);
+        assert_eq!(automaton.grid().map, parse("##..\n#...\n....").map);
+    }
+}
@@ -0,0 +1,359 @@
+use std::fmt;
+
+use super::coord::Coord;
+use super::direction::Direction;
+use super::grid::Grid;
+use super::neighbourhood::Neighbourhood;
+use super::orientation::{Oriented, YDown};
+use super::point::Point;
+use super::transform::Symmetry;
+
+const WORD: usize = 64;
+
+/// Grid of booleans packed into `u64` words. Every row starts on a new word,
+/// bit `x % 64` of word `x / 64` of the row is the cell in column `x`. Bits
+/// past the width are always 0.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct BitGrid {
+    words: Vec<u64>,
+    pub width: usize,
+    pub height: usize,
+    words_per_row: usize,
+}
+
+/// Same orientation as [`Grid`], rows are stored top to bottom.
+impl Oriented for BitGrid {
+    type Orientation = YDown;
+}
+
+impl BitGrid {
+    pub fn new(width: usize, height: usize) -> Self {
+        let words_per_row = width.div_ceil(WORD);
+        BitGrid { words: vec![0; words_per_row * height], width, height, words_per_row }
+    }
+
+    pub fn from_grid<V>(grid: &Grid<V>, is_set: impl Fn(&V) -> bool) -> Self {
+        let mut bits = BitGrid::new(grid.width, grid.height);
+        for point in grid.iter_points() {
+            if is_set(point.value) {
+                bits.set(&point.coord, true);
+            }
+        }
+        bits
+    }
+
+    pub fn to_grid(&self) -> Grid<bool> {
+        Grid { map: self.iter_values().collect(), width: self.width, height: self.height }
+    }
+
+    pub fn contains(&self, coord: &Coord<usize>) -> bool {
+        coord.x < self.width && coord.y < self.height
+    }
+
+    fn position(&self, coord: &Coord<usize>) -> (usize, u64) {
+        assert!(self.contains(coord), "{:?} is out of the grid", coord);
+        (coord.y * self.words_per_row + coord.x / WORD, 1 << (coord.x % WORD))
+    }
+
+    pub fn get_val(&self, coord: &Coord<usize>) -> bool {
+        let (word, bit) = self.position(coord);
+        self.words[word] & bit != 0
+    }
+
+    pub fn set(&mut self, coord: &Coord<usize>, value: bool) {
+        let (word, bit) = self.position(coord);
+        if value {
+            self.words[word] |= bit;
+        } else {
+            self.words[word] &= !bit;
+        }
+    }
+
+    pub fn toggle(&mut self, coord: &Coord<usize>) {
+        let (word, bit) = self.position(coord);
+        self.words[word] ^= bit;
+    }
+
+    pub fn get_neighbour(&self, coord: &Coord<usize>, direction: &Direction) -> Option<Coord<usize>> {
+        let step: Coord<isize> = Self::unit_step(direction);
+        let x = coord.x.checked_add_signed(step.x).filter(|x| *x < self.width)?;
+        let y = coord.y.checked_add_signed(step.y).filter(|y| *y < self.height)?;
+        Some(Coord::new(x, y))
+    }
+
+    pub fn neighbours(&self, coord: Coord<usize>, neighbourhood: Neighbourhood) -> impl Iterator<Item = Coord<usize>> + '_ {
+        neighbourhood.directions().iter().filter_map(move |direction| {
+            if neighbourhood.wrapping() {
+                let step: Coord<isize> = Self::unit_step(direction);
+                let x = (coord.x as isize + step.x).rem_euclid(self.width as isize) as usize;
+                let y = (coord.y as isize + step.y).rem_euclid(self.height as isize) as usize;
+                Some(Coord::new(x, y))
+            } else {
+                self.get_neighbour(&coord, direction)
+            }
+        })
+    }
+
+    pub fn iter_coords(&self) -> impl Iterator<Item = Coord<usize>> + '_ {
+        (0..self.height).flat_map(move |y| (0..self.width).map(move |x| Coord::new(x, y)))
+    }
+
+    pub fn iter_values(&self) -> impl Iterator<Item = bool> + '_ {
+        self.iter_coords().map(|c| self.get_val(&c))
+    }
+
+    pub fn iter_points(&self) -> impl Iterator<Item = Point<usize, bool>> + '_ {
+        self.iter_coords().map(|c| Point::from_coord(c, self.get_val(&c)))
+    }
+
+    /// Coordinates of the set cells, skipping empty words.
+    pub fn iter_ones(&self) -> impl Iterator<Item = Coord<usize>> + '_ {
+        self.words.iter().enumerate().flat_map(move |(i, word)| {
+            let (y, x0) = (i / self.words_per_row, (i % self.words_per_row) * WORD);
+            let mut bits = *word;
+            std::iter::from_fn(move || {
+                if bits == 0 {
+                    return None;
+                }
+                let x = bits.trailing_zeros() as usize;
+                bits &= bits - 1;
+                Some(Coord::new(x0 + x, y))
+            })
+        })
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn row_words(&self, y: usize) -> &[u64] {
+        &self.words[y * self.words_per_row..(y + 1) * self.words_per_row]
+    }
+
+    /// Words of the row, bits past the width have to stay 0.
+    pub fn row_words_mut(&mut self, y: usize) -> &mut [u64] {
+        &mut self.words[y * self.words_per_row..(y + 1) * self.words_per_row]
+    }
+
+    pub fn count_row(&self, y: usize) -> usize {
+        self.row_words(y).iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn and(&self, other: &BitGrid) -> BitGrid {
+        self.zip_words(other, |a, b| a & b)
+    }
+
+    pub fn or(&self, other: &BitGrid) -> BitGrid {
+        self.zip_words(other, |a, b| a | b)
+    }
+
+    pub fn xor(&self, other: &BitGrid) -> BitGrid {
+        self.zip_words(other, |a, b| a ^ b)
+    }
+
+    /// Cells set in `self` but not in `other`.
+    pub fn and_not(&self, other: &BitGrid) -> BitGrid {
+        self.zip_words(other, |a, b| a & !b)
+    }
+
+    pub fn not(&self) -> BitGrid {
+        let mut result = self.clone();
+        result.words.iter_mut().for_each(|w| *w = !*w);
+        result.clear_padding();
+        result
+    }
+
+    fn zip_words(&self, other: &BitGrid, op: impl Fn(u64, u64) -> u64) -> BitGrid {
+        assert_eq!((self.width, self.height), (other.width, other.height), "Grids are not the same size");
+        let words = self.words.iter().zip(&other.words).map(|(a, b)| op(*a, *b)).collect();
+        BitGrid { words, ..*self }
+    }
+
+    fn clear_padding(&mut self) {
+        if self.width.is_multiple_of(WORD) {
+            return;
+        }
+        let mask = (1 << (self.width % WORD)) - 1;
+        for y in 0..self.height {
+            self.words[(y + 1) * self.words_per_row - 1] &= mask;
+        }
+    }
+
+    /// Grid where every cell holds the value of the cell `(dx, dy)` away
+    /// from it, cells past the edge are 0. Rows are shifted word-wise.
+    pub fn shifted(&self, dx: isize, dy: isize) -> BitGrid {
+        let mut result = BitGrid::new(self.width, self.height);
+        let (whole, bits) = ((dx.unsigned_abs()) / WORD, (dx.unsigned_abs() % WORD) as u32);
+        for y in 0..self.height {
+            let Some(source_y) = y.checked_add_signed(dy).filter(|sy| *sy < self.height) else {
+                continue;
+            };
+            let source = self.row_words(source_y);
+            let n = self.words_per_row;
+            let word = |i: isize| if i >= 0 && (i as usize) < n { source[i as usize] } else { 0 };
+            let target = result.row_words_mut(y);
+            for (i, target) in target.iter_mut().enumerate() {
+                let i = i as isize;
+                *target = if dx >= 0 {
+                    // Bit x takes bit x + dx, the words move down
+                    let low = word(i + whole as isize);
+                    let high = word(i + whole as isize + 1);
+                    if bits == 0 { low } else { (low >> bits) | (high << (WORD as u32 - bits)) }
+                } else {
+                    let high = word(i - whole as isize);
+                    let low = word(i - whole as isize - 1);
+                    if bits == 0 { high } else { (high << bits) | (low >> (WORD as u32 - bits)) }
+                };
+            }
+        }
+        result.clear_padding();
+        result
+    }
+
+    /// Number of set neighbours of every cell, counted word-wise.
+    pub fn neighbour_counts(&self, neighbourhood: Neighbourhood) -> NeighbourCounts {
+        assert!(!neighbourhood.wrapping(), "Wrapping neighbourhoods are not supported");
+        let mut planes: [BitGrid; 4] = std::array::from_fn(|_| BitGrid::new(self.width, self.height));
+        for direction in neighbourhood.directions() {
+            let step: Coord<isize> = Self::unit_step(direction);
+            let mut carry = self.shifted(step.x, step.y).words;
+            // Bit-sliced addition of the shifted grid to the counters
+            for plane in planes.iter_mut() {
+                for (p, c) in plane.words.iter_mut().zip(carry.iter_mut()) {
+                    let next = *p & *c;
+                    *p ^= *c;
+                    *c = next;
+                }
+            }
+        }
+        NeighbourCounts { planes }
+    }
+
+    pub fn transform(&mut self, symmetry: Symmetry) {
+        let (width, height) = if symmetry.swaps_dimensions() {
+            (self.height, self.width)
+        } else {
+            (self.width, self.height)
+        };
+        let mut result = BitGrid::new(width, height);
+        for coord in result.iter_coords().collect::<Vec<_>>() {
+            let (x, y) = symmetry.source(coord.x, coord.y, self.width, self.height);
+            if self.get_val(&Coord::new(x, y)) {
+                result.set(&coord, true);
+            }
+        }
+        *self = result;
+    }
+
+    pub fn rotate(&mut self, clockwise: bool) {
+        self.transform(if clockwise { Symmetry::Rotate90 } else { Symmetry::Rotate270 });
+    }
+}
+
+/// Neighbour counts of a [`BitGrid`] as four bit planes, bit `i` of every
+/// count is in plane `i`.
+pub struct NeighbourCounts {
+    planes: [BitGrid; 4],
+}
+
+impl NeighbourCounts {
+    pub fn get(&self, coord: &Coord<usize>) -> usize {
+        self.planes.iter().enumerate().map(|(i, plane)| (plane.get_val(coord) as usize) << i).sum()
+    }
+
+    /// Cells with exactly `count` neighbours.
+    pub fn equal(&self, count: usize) -> BitGrid {
+        let mut result = BitGrid::new(self.planes[0].width, self.planes[0].height);
+        result.words.iter_mut().enumerate().for_each(|(w, word)| {
+            *word = self.planes.iter().enumerate().fold(u64::MAX, |acc, (i, plane)| {
+                if count >> i & 1 == 1 { acc & plane.words[w] } else { acc & !plane.words[w] }
+            });
+        });
+        result.clear_padding();
+        result
+    }
+
+    /// Cells with less than `count` neighbours.
+    pub fn less_than(&self, count: usize) -> BitGrid {
+        let empty = BitGrid::new(self.planes[0].width, self.planes[0].height);
+        (0..count.min(9)).fold(empty, |acc, c| acc.or(&self.equal(c)))
+    }
+}
+
+impl fmt::Display for BitGrid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for y in 0..self.height {
+            for x in 0..self.width {
+                write!(f, "{}", if self.get_val(&Coord::new(x, y)) { '#' } else { '.' })?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::random::Rng;
+
+    fn random(rng: &mut Rng, width: usize, height: usize) -> BitGrid {
+        let grid = Grid { map: (0..width * height).map(|_| rng.chance(1, 2)).collect(), width, height };
+        BitGrid::from_grid(&grid, |v| *v)
+    }
+
+    #[test]
+    fn test_cells() {
+        let grid: Grid<char> = Grid::parse("#..#\n.##.").unwrap();
+        let mut bits = BitGrid::from_grid(&grid, |c| *c == '#');
+        assert_eq!(bits.to_string(), "#..#\n.##.\n");
+        assert_eq!(bits.count_ones(), 4);
+        assert_eq!(bits.iter_ones().collect::<Vec<_>>(), vec![
+            Coord::new(0, 0), Coord::new(3, 0), Coord::new(1, 1), Coord::new(2, 1)
+        ]);
+        bits.toggle(&Coord::new(0, 0));
+        bits.set(&Coord::new(1, 0), true);
+        assert_eq!(bits.count_row(0), 2);
+        assert_eq!(bits.not().to_string(), "#.#.\n#..#\n");
+        bits.rotate(true);
+        assert_eq!(bits.to_string(), "..\n##\n#.\n.#\n");
+    }
+
+    #[test]
+    #[should_panic(expected = "out of the grid")]
+    fn test_set_past_width() {
+        let mut bits = BitGrid::new(3, 2);
+        bits.set(&Coord::new(3, 0), true);
+    }
+
+    #[test]
+    fn test_shifted_across_words() {
+        let mut rng = Rng::new(40);
+        let bits = random(&mut rng, 150, 3);
+        for (dx, dy) in [(1, 0), (-1, 0), (64, 1), (-65, -1), (130, 0), (-3, 2)] {
+            let shifted = bits.shifted(dx, dy);
+            for coord in bits.iter_coords() {
+                let source = Coord::new(coord.x as isize + dx, coord.y as isize + dy);
+                let expected = source.x >= 0 && source.y >= 0
+                    && bits.contains(&Coord::new(source.x as usize, source.y as usize))
+                    && bits.get_val(&Coord::new(source.x as usize, source.y as usize));
+                assert_eq!(shifted.get_val(&coord), expected, "{:?} {:?}", (dx, dy), coord);
+            }
+        }
+    }
+
+    #[test]
+    fn test_neighbour_counts() {
+        let mut rng = Rng::new(41);
+        let bits = random(&mut rng, 70, 5);
+        for neighbourhood in [Neighbourhood::Touching, Neighbourhood::Adjacent] {
+            let counts = bits.neighbour_counts(neighbourhood);
+            let fewer = counts.less_than(4);
+            for coord in bits.iter_coords() {
+                let expected = bits.neighbours(coord, neighbourhood).filter(|n| bits.get_val(n)).count();
+                assert_eq!(counts.get(&coord), expected);
+                assert_eq!(fewer.get_val(&coord), expected < 4);
+            }
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use std::error::Error;
+use std::fmt;
+
+/// Value that can be parsed from a single character of the puzzle input.
+pub trait FromCell: Sized {
+    fn from_cell(c: char) -> Option<Self>;
+}
+
+/// Value that can be printed as a single character, the inverse of
+/// [`FromCell`].
+pub trait ToCell {
+    fn to_cell(&self) -> char;
+}
+
+impl FromCell for char {
+    fn from_cell(c: char) -> Option<Self> {
+        Some(c)
+    }
+}
+
+impl ToCell for char {
+    fn to_cell(&self) -> char {
+        *self
+    }
+}
+
+/// Digit grids, `'0'..='9'`.
+impl FromCell for u8 {
+    fn from_cell(c: char) -> Option<Self> {
+        c.to_digit(10).map(|d| d as u8)
+    }
+}
+
+impl ToCell for u8 {
+    fn to_cell(&self) -> char {
+        char::from_digit(*self as u32, 10).unwrap_or('?')
+    }
+}
+
+/// Error of parsing a grid from text. Lines and columns are 1-based.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseGridError {
+    Empty,
+    RaggedRow { line: usize, width: usize, expected: usize },
+    UnknownCell { line: usize, column: usize, cell: char },
+}
+
+impl fmt::Display for ParseGridError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseGridError::Empty => write!(f, "Grid is empty"),
+            ParseGridError::RaggedRow { line, width, expected } => {
+                write!(f, "Line {} width {} is not the same as all the rest ({})", line, width, expected)
+            },
+            ParseGridError::UnknownCell { line, column, cell } => {
+                write!(f, "Unknown cell `{}` at line {}, column {}", cell, line, column)
+            }
+        }
+    }
+}
+
+impl Error for ParseGridError {}
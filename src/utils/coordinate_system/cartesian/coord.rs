@@ -5,6 +5,7 @@ use funty::Signed;
 
 use super::axis::Axis;
 use super::direction::Direction;
+use super::orientation::{Orientation, YAxis, YUp};
 
 
 #[derive(Clone, Copy, PartialEq, Eq, Hash)]
@@ -46,16 +47,33 @@ impl<T> Coord<T>
 where
     T: num::Integer + Copy
 {
+    /// Neighbour in the direction in the [`YUp`] space, see
+    /// [`Coord::step`] for the other one.
     pub fn get_neighbour(&self, direction: &Direction) -> Self {
+        self.step::<YUp>(direction)
+    }
+
+    /// Neighbour in the direction in the space `O`.
+    pub fn step<O: Orientation>(&self, direction: &Direction) -> Self {
+        let one = num::one();
+        // Computed only when needed, unsigned coordinates can't go below 0
+        let north = || match O::Y_AXIS {
+            YAxis::Up => self.y + one,
+            YAxis::Down => self.y - one,
+        };
+        let south = || match O::Y_AXIS {
+            YAxis::Up => self.y - one,
+            YAxis::Down => self.y + one,
+        };
         match direction {
-            Direction::N => Coord::new(self.x, self.y + num::one()),
-            Direction::NE => Coord::new(self.x + num::one(), self.y + num::one()),
-            Direction::E => Coord::new(self.x + num::one(), self.y),
-            Direction::SE => Coord::new(self.x + num::one(), self.y - num::one()),
-            Direction::S => Coord::new(self.x, self.y - num::one()),
-            Direction::SW => Coord::new(self.x - num::one(), self.y - num::one()),
-            Direction::W => Coord::new(self.x - num::one(), self.y),
-            Direction::NW => Coord::new(self.x - num::one(), self.y + num::one()),
+            Direction::N => Coord::new(self.x, north()),
+            Direction::NE => Coord::new(self.x + one, north()),
+            Direction::E => Coord::new(self.x + one, self.y),
+            Direction::SE => Coord::new(self.x + one, south()),
+            Direction::S => Coord::new(self.x, south()),
+            Direction::SW => Coord::new(self.x - one, south()),
+            Direction::W => Coord::new(self.x - one, self.y),
+            Direction::NW => Coord::new(self.x - one, north()),
         }
     }
 }
@@ -124,21 +142,13 @@ impl<T: PartialOrd + Ord> PartialOrd for Coord<T> {
     }
 }
 
+/// Unit step of the direction in the [`YUp`] space.
 impl<T> From<&Direction> for Coord<T>
 where 
     T: num::Signed
 {
     fn from(direction: &Direction) -> Self {
-        match direction {
-            Direction::N  => Coord::new( T::zero(), T::one()), 
-            Direction::E  => Coord::new( T::one(),  T::zero()),
-            Direction::S  => Coord::new( T::zero(),-T::one()),
-            Direction::W  => Coord::new(-T::one(),  T::zero()),
-            Direction::NE => Coord::new( T::one(),  T::one()),
-            Direction::NW => Coord::new(-T::one(),  T::one()),
-            Direction::SE => Coord::new( T::one(), -T::one()),
-            Direction::SW => Coord::new(-T::one(), -T::one()),
-        }
+        direction.offset::<YUp, T>()
     }
 }
 
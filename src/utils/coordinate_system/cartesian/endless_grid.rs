@@ -1,73 +1,265 @@
+use std::collections::HashMap;
 use std::fmt;
+use std::marker::PhantomData;
 
 use funty::Integral;
-use num::{Num, ToPrimitive};
 
+use super::cell::ToCell;
 use super::coord::Coord;
+use super::grid::Grid as DenseGrid;
+use super::orientation::{Orientation, Oriented, YAxis, YDown};
 
-#[derive(Clone)]
-pub struct Grid<V> {
-    pub coordinates: Vec<Coord<V>>,
-    pub min_x: V,
-    pub max_x: V,
-    pub min_y: V,
-    pub max_y: V,
-    pub height: V,
-    pub width: V
+/// Unbounded sparse grid. Only the set cells are stored, every other cell
+/// has the default value. The coordinates are in the space `O`, rows are
+/// displayed with north on top.
+#[derive(Clone, Debug)]
+pub struct Grid<K, V, O = YDown> {
+    cells: HashMap<Coord<K>, V>,
+    default: V,
+    /// Smallest and largest coordinates of the set cells
+    bounds: Option<(Coord<K>, Coord<K>)>,
+    orientation: PhantomData<O>,
+}
+
+/// Distance from `from` up to `to`, computed wider than the keys so the
+/// span of e.g. `i8` keys from -100 to 100 doesn't overflow.
+fn distance<K: Integral>(from: K, to: K) -> usize {
+    (to.as_i128() - from.as_i128()) as usize
+}
 
+impl<K, V, O> Oriented for Grid<K, V, O>
+where O: Orientation
+{
+    type Orientation = O;
 }
 
-impl<V> Grid<V>
-where V: Integral
+impl<K, V, O> Grid<K, V, O>
+where
+    K: Integral,
+    O: Orientation
 {
-    pub fn new(coordinates: Vec<Coord<V>>) -> Self
+    pub fn new(default: V) -> Self {
+        Grid { cells: HashMap::new(), default, bounds: None, orientation: PhantomData }
+    }
+
+    /// Same cells read in the space `P`, the coordinates are kept as they
+    /// are.
+    pub fn with_orientation<P: Orientation>(self) -> Grid<K, V, P> {
+        Grid { cells: self.cells, default: self.default, bounds: self.bounds, orientation: PhantomData }
+    }
+
+    /// Value of the cell, the default for unset cells.
+    pub fn get(&self, coord: &Coord<K>) -> &V {
+        self.cells.get(coord).unwrap_or(&self.default)
+    }
+
+    pub fn get_mut(&mut self, coord: &Coord<K>) -> Option<&mut V> {
+        self.cells.get_mut(coord)
+    }
+
+    pub fn is_set(&self, coord: &Coord<K>) -> bool {
+        self.cells.contains_key(coord)
+    }
+
+    pub fn insert(&mut self, coord: Coord<K>, value: V) -> Option<V> {
+        self.bounds = Some(match self.bounds {
+            None => (coord, coord),
+            Some((min, max)) => (
+                Coord::new(min.x.min(coord.x), min.y.min(coord.y)),
+                Coord::new(max.x.max(coord.x), max.y.max(coord.y)),
+            ),
+        });
+        self.cells.insert(coord, value)
+    }
+
+    pub fn remove(&mut self, coord: &Coord<K>) -> Option<V> {
+        let removed = self.cells.remove(coord)?;
+        // Only cells on the edge of the bounding box can shrink it
+        if let Some((min, max)) = self.bounds
+            && (coord.x == min.x || coord.x == max.x || coord.y == min.y || coord.y == max.y) {
+            self.bounds = self.cells.keys().fold(None, |bounds, c| Some(match bounds {
+                None => (*c, *c),
+                Some((min, max)) => (
+                    Coord::new(min.x.min(c.x), min.y.min(c.y)),
+                    Coord::new(max.x.max(c.x), max.y.max(c.y)),
+                ),
+            }));
+        }
+        Some(removed)
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// Top left and bottom right corners of the set cells, inclusive.
+    pub fn bounds(&self) -> Option<(Coord<K>, Coord<K>)> {
+        self.bounds
+    }
+
+    pub fn width(&self) -> usize {
+        self.bounds.map_or(0, |(min, max)| distance(min.x, max.x) + 1)
+    }
+
+    pub fn height(&self) -> usize {
+        self.bounds.map_or(0, |(min, max)| distance(min.y, max.y) + 1)
+    }
+
+    /// Set cells in display order, row by row from the top.
+    pub fn iter(&self) -> impl Iterator<Item = (Coord<K>, &V)> {
+        let mut cells: Vec<_> = self.cells.iter().map(|(c, v)| (*c, v)).collect();
+        match O::Y_AXIS {
+            YAxis::Down => cells.sort_unstable_by_key(|(c, _)| (c.y, c.x)),
+            YAxis::Up => cells.sort_unstable_by_key(|(c, _)| (std::cmp::Reverse(c.y), c.x)),
+        }
+        cells.into_iter()
+    }
+}
+
+/// Dense grids are in the [`YDown`] space, so only grids in the same space
+/// convert to and from them.
+impl<K, V> Grid<K, V, YDown>
+where K: Integral
+{
+    /// Dense grid of the bounding box and the coordinate of it's top left
+    /// corner, `None` when nothing is set.
+    pub fn to_dense(&self) -> Option<(Coord<K>, DenseGrid<V>)>
+    where V: Clone
     {
-        let mut min_x = V::MAX;
-        let mut max_x = V::MIN;
-        let mut min_y = V::MAX;
-        let mut max_y = V::MIN;
-        for coord in &coordinates {
-            if coord.x > max_x {
-                max_x = coord.x;                
-            } else if coord.x < min_x {
-                min_x = coord.x;
-            }
+        let (min, _) = self.bounds?;
+        let (width, height) = (self.width(), self.height());
+        let mut map = vec![self.default.clone(); width * height];
+        for (coord, value) in &self.cells {
+            map[distance(min.y, coord.y) * width + distance(min.x, coord.x)] = value.clone();
+        }
+        Some((min, DenseGrid { map, width, height }))
+    }
 
-            if coord.y > max_y {
-                max_y = coord.y;
-            } else if coord.y < min_y {
-                min_y = coord.y;
+    /// Cells of the dense grid placed with it's top left corner at `origin`,
+    /// cells equal to the default are left unset.
+    pub fn from_dense(grid: &DenseGrid<V>, origin: Coord<K>, default: V) -> Self
+    where V: Clone + PartialEq
+    {
+        let mut endless = Grid::new(default);
+        for point in grid.iter_points() {
+            if *point.value == endless.default {
+                continue;
             }
+            let (Ok(x), Ok(y)) = (K::try_from(point.coord.x), K::try_from(point.coord.y)) else {
+                panic!("Coordinate {} does not fit the key type", point.coord);
+            };
+            endless.insert(Coord::new(origin.x + x, origin.y + y), point.value.clone());
         }
-        let height = max_y - min_y;
-        let width = max_x - min_x;
-
-        Self { coordinates, min_x, max_x, min_y, max_y, height, width }
+        endless
     }
 }
 
-impl<V> fmt::Display for Grid<V>
-where 
-    V: fmt::Display + std::cmp::PartialOrd + Num + Clone + Copy + ToPrimitive
+impl<K, V, O> fmt::Display for Grid<K, V, O>
+where
+    K: Integral,
+    V: ToCell,
+    O: Orientation
 {
+    // Only the bounding box is printed. Every row is filled with the
+    // default and the set cells are written over it.
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "\n")?;
-        let mut y = self.min_y;
-        while y <= self.max_y {
-            let mut x = self.min_x;
-            let mut line: String = String::with_capacity(num::ToPrimitive::to_usize(&self.width).unwrap());
-            while x <= self.max_x {
-                let c: Coord<V> = Coord::new(x.clone(), y.clone());
-                if self.coordinates.contains(&c) {
-                    line.push('×');
-                } else {
-                    line.push(' ');
-                }
-                x = x + num::one();
-            }
-            write!(f, "{}\n", line)?;
-            y = y + num::one();
+        let Some((min, max)) = self.bounds else {
+            return Ok(());
+        };
+        let empty: Vec<char> = vec![self.default.to_cell(); self.width()];
+        let mut rows: Vec<Vec<char>> = vec![empty; self.height()];
+        for (coord, value) in &self.cells {
+            let row = match O::Y_AXIS {
+                YAxis::Down => distance(min.y, coord.y),
+                YAxis::Up => distance(coord.y, max.y),
+            };
+            rows[row][distance(min.x, coord.x)] = value.to_cell();
         }
-        write!(f, "\n")
+        for row in rows {
+            writeln!(f, "{}", row.into_iter().collect::<String>())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::orientation::YUp;
+
+    #[test]
+    fn test_bounds() {
+        let mut grid: Grid<i32, char> = Grid::new('.');
+        assert_eq!(grid.bounds(), None);
+        grid.insert(Coord::new(3, -2), '#');
+        assert_eq!(grid.bounds(), Some((Coord::new(3, -2), Coord::new(3, -2))));
+        grid.insert(Coord::new(-1, 4), '#');
+        grid.insert(Coord::new(0, 0), '#');
+        assert_eq!(grid.bounds(), Some((Coord::new(-1, -2), Coord::new(3, 4))));
+        assert_eq!((grid.width(), grid.height()), (5, 7));
+
+        grid.remove(&Coord::new(3, -2));
+        assert_eq!(grid.bounds(), Some((Coord::new(-1, 0), Coord::new(0, 4))));
+        assert_eq!(grid.remove(&Coord::new(3, -2)), None);
+        grid.remove(&Coord::new(-1, 4));
+        grid.remove(&Coord::new(0, 0));
+        assert_eq!(grid.bounds(), None);
+    }
+
+    #[test]
+    fn test_wide_span_of_narrow_keys() {
+        let mut grid: Grid<i8, char> = Grid::new('.');
+        grid.insert(Coord::new(-100, 100), '#');
+        grid.insert(Coord::new(100, -100), '#');
+        assert_eq!((grid.width(), grid.height()), (201, 201));
+        let (origin, dense) = grid.to_dense().unwrap();
+        assert_eq!(origin, Coord::new(-100, -100));
+        assert_eq!(*dense.get_val(&Coord::new(200, 0)), '#');
+        assert_eq!(grid.to_string().lines().count(), 201);
+    }
+
+    #[test]
+    fn test_values_and_order() {
+        let mut grid: Grid<i64, u8> = Grid::new(0);
+        grid.insert(Coord::new(5, 1), 3);
+        grid.insert(Coord::new(-5, 1), 2);
+        grid.insert(Coord::new(0, -1), 1);
+        assert_eq!(*grid.get(&Coord::new(5, 1)), 3);
+        assert_eq!(*grid.get(&Coord::new(100, 100)), 0);
+        *grid.get_mut(&Coord::new(0, -1)).unwrap() = 4;
+
+        let values: Vec<_> = grid.iter().map(|(_, v)| *v).collect();
+        assert_eq!(values, vec![4, 2, 3]);
+        let grid: Grid<i64, u8, YUp> = grid.with_orientation();
+        let values: Vec<_> = grid.iter().map(|(_, v)| *v).collect();
+        assert_eq!(values, vec![2, 3, 4]);
+    }
+
+    #[test]
+    fn test_dense_round_trip() {
+        let dense: DenseGrid<char> = DenseGrid::parse("..#\n#..").unwrap();
+        let endless = Grid::from_dense(&dense, Coord::new(-10isize, 7), '.');
+        assert_eq!(endless.len(), 2);
+        assert!(endless.is_set(&Coord::new(-8, 7)));
+        assert_eq!(endless.to_string(), "..#\n#..\n");
+
+        let (origin, back) = endless.to_dense().unwrap();
+        assert_eq!(origin, Coord::new(-10, 7));
+        assert_eq!(back.map, dense.map);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_large_sparse_display() {
+        let mut grid: Grid<i32, char> = Grid::new(' ');
+        grid.insert(Coord::new(-500, -500), 'a');
+        grid.insert(Coord::new(500, 500), 'b');
+        let display = grid.to_string();
+        assert_eq!(display.lines().count(), 1001);
+        assert!(display.starts_with('a'));
+        assert!(display.trim_end().ends_with('b'));
+    }
+}
@@ -1,19 +1,29 @@
 use std::fmt;
 
+use owo_colors::Style;
+
 use super::coord::Coord;
 use super::point::Point;
-use super::direction::{Direction, DIRECTIONS, TOUCHING_DIRECTIONS};
-use super::axis::Axis;
+use super::direction::{Direction, TOUCHING_DIRECTIONS};
+use super::orientation::{Oriented, YDown};
+use super::neighbourhood::Neighbourhood;
+use super::render::GridRenderer;
+use super::cell::{FromCell, ToCell, ParseGridError};
 
 
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Grid<V> {
     pub map: Vec<V>,
     pub height: usize,
     pub width: usize
 }
 
+/// Rows are stored top to bottom, north is the previous row.
+impl<V> Oriented for Grid<V> {
+    type Orientation = YDown;
+}
+
 impl<V> Grid<V>
 {
     pub fn new(map: Vec<Vec<V>>) -> Self {
@@ -55,6 +65,36 @@ impl<V> Grid<V>
         }
     }
 
+    /// Neighbour in the direction, off the edge it continues on the
+    /// opposite side of the grid.
+    pub fn get_neighbour_wrapping(&self, coord: &Coord<usize>, direction: &Direction) -> Coord<usize> {
+        let up = |v: usize, len: usize| if v == len - 1 { 0 } else { v + 1 };
+        let down = |v: usize, len: usize| if v == 0 { len - 1 } else { v - 1 };
+        let (x, y) = (coord.x, coord.y);
+        let (w, h) = (self.width, self.height);
+        match direction {
+            Direction::N => Coord::new(x, down(y, h)),
+            Direction::E => Coord::new(up(x, w), y),
+            Direction::S => Coord::new(x, up(y, h)),
+            Direction::W => Coord::new(down(x, w), y),
+            Direction::NE => Coord::new(up(x, w), down(y, h)),
+            Direction::SE => Coord::new(up(x, w), up(y, h)),
+            Direction::SW => Coord::new(down(x, w), up(y, h)),
+            Direction::NW => Coord::new(down(x, w), down(y, h)),
+        }
+    }
+
+    /// Coordinate with `y` growing to the north and the origin in the
+    /// bottom left corner.
+    pub fn to_y_up(&self, coord: &Coord<usize>) -> Coord<usize> {
+        Coord::new(coord.x, self.height - 1 - coord.y)
+    }
+
+    /// Inverse of [`Grid::to_y_up`].
+    pub fn from_y_up(&self, coord: &Coord<usize>) -> Coord<usize> {
+        self.to_y_up(coord)
+    }
+
     pub fn get_index(&self, coord: &Coord<usize>) -> usize {
         coord.y * self.width + coord.x
     }
@@ -76,60 +116,24 @@ impl<V> Grid<V>
     }
 
     pub fn neighbour_coords(&self, coord: &Coord<usize>) -> Vec<Coord<usize>> {
-        TOUCHING_DIRECTIONS
-            .iter()
-            .filter_map(|direction| {
-                self.get_neighbour(coord, direction)
-            }).collect()
+        self.neighbours(*coord, Neighbourhood::Touching).collect()
     }
 
-    pub fn neigbour_coords_optional(&self, coord: &Coord<usize>) -> Vec<Option<Coord<usize>>> {
-        TOUCHING_DIRECTIONS
-            .iter()
-            .map(|direction| {
-                self.get_neighbour(coord, direction)
-            }).collect()
+    /// Neighbours in the order of [`TOUCHING_DIRECTIONS`], `None` off the
+    /// edge of the map.
+    pub fn neigbour_coords_optional(&self, coord: &Coord<usize>) -> [Option<Coord<usize>>; 4] {
+        TOUCHING_DIRECTIONS.map(|direction| self.get_neighbour(coord, &direction))
     }
 
     /// Get's neighbour coords of a specified coordinate. If the neighbour
     /// coordinate is off the edge of map, it returns the one on the opposite 
     /// end of map.
     pub fn neighbour_coords_wrapping(&self, coord: &Coord<usize>) -> Vec<Coord<usize>> {
-        let mut neighbours = vec![];
-        // North
-        if coord.y == 0 {
-            neighbours.push(Coord::new(coord.x, self.height - 1));
-        } else {
-            neighbours.push(Coord::new(coord.x, coord.y - 1));
-        }
-        // East
-        if coord.x == self.width - 1 {
-            neighbours.push(Coord::new(0, coord.y));
-        } else {
-            neighbours.push(Coord::new(coord.x + 1, coord.y));
-        }
-        // South
-        if coord.y == self.height - 1 {
-            neighbours.push(Coord::new(coord.x, 0));
-        } else {
-            neighbours.push(Coord::new(coord.x, coord.y + 1));
-        }
-
-        // West
-        if coord.x == 0 {
-            neighbours.push(Coord::new(self.width - 1, coord.y));
-        } else {
-            neighbours.push(Coord::new(coord.x - 1, coord.y));
-        }        
-        neighbours
+        self.neighbours(*coord, Neighbourhood::TouchingWrapping).collect()
     }
 
     pub fn adjacent_coords(&self, coord: &Coord<usize>) -> Vec<Coord<usize>> {
-        DIRECTIONS
-            .iter()
-            .filter_map(|direction| {
-                self.get_neighbour(coord, direction)
-            }).collect()
+        self.neighbours(*coord, Neighbourhood::Adjacent).collect()
     }
 
     pub fn iter_values(&self) -> impl Iterator<Item=&V> {
@@ -163,67 +167,110 @@ impl<V> Grid<V>
     }
 
     pub fn direction_iter(&self, direction: Direction, current_coord: Coord<usize>) -> GridDirectionIterator {
-        let axis = direction.affected_axes()[0];
+        self.step_iter(grid_step(&direction), current_coord)
+    }
+
+    pub fn wrapped_direction_iter(&self, direction: Direction, current_coord: Coord<usize>) -> GridWrappedDirectionIterator {
+        self.wrapped_step_iter(grid_step(&direction), current_coord)
+    }
+
+    /// Coordinates from the one after `current_coord` to the edge of the
+    /// grid, moving by `(dx, dy)` every step, e.g. `(1, 2)` for a knight.
+    pub fn step_iter(&self, step: (isize, isize), current_coord: Coord<usize>) -> GridDirectionIterator {
+        assert!(step != (0, 0), "Step (0, 0) never leaves {}", current_coord);
         GridDirectionIterator {
             height: self.height,
             width: self.width,
-            direction, current_coord, axis
+            step, current_coord
         }
     }
 
-    pub fn wrapped_direction_iter(&self, direction: Direction, current_coord: Coord<usize>) -> GridWrappedDirectionIterator {
-        let axis = direction.affected_axes()[0];
-        GridWrappedDirectionIterator { 
-            height: self.height, 
-            width: self.width, 
-            direction, current_coord, axis 
+    pub fn wrapped_step_iter(&self, step: (isize, isize), current_coord: Coord<usize>) -> GridWrappedDirectionIterator {
+        assert!(step != (0, 0), "Step (0, 0) never leaves {}", current_coord);
+        GridWrappedDirectionIterator {
+            height: self.height,
+            width: self.width,
+            step, current_coord
         }
     }
+
+    /// Points of the cells in the direction, up to the edge of the grid.
+    pub fn ray(&self, direction: Direction, from: Coord<usize>) -> impl Iterator<Item=Point<usize, &V>> + '_ {
+        self.direction_iter(direction, from).map(|c| self.get_point(&c))
+    }
+
+    /// Endless, see [`Grid::wrapped_direction_iter`].
+    pub fn wrapped_ray(&self, direction: Direction, from: Coord<usize>) -> impl Iterator<Item=Point<usize, &V>> + '_ {
+        self.wrapped_direction_iter(direction, from).map(|c| self.get_point(&c))
+    }
+
+    pub fn step_ray(&self, step: (isize, isize), from: Coord<usize>) -> impl Iterator<Item=Point<usize, &V>> + '_ {
+        self.step_iter(step, from).map(|c| self.get_point(&c))
+    }
+
+    pub fn wrapped_step_ray(&self, step: (isize, isize), from: Coord<usize>) -> impl Iterator<Item=Point<usize, &V>> + '_ {
+        self.wrapped_step_iter(step, from).map(|c| self.get_point(&c))
+    }
+}
+
+fn grid_step(direction: &Direction) -> (isize, isize) {
+    let offset: Coord<isize> = Grid::<()>::unit_step(direction);
+    (offset.x, offset.y)
 }
 
 impl<V> Grid<V>
-where V: Copy
- {
-    pub fn rotate(&mut self, clockwise: bool) {
-        let new_map: Vec<V> = if clockwise {
-            (0..self.width).map(|x| {
-                (0..self.height).rev().map(|y| {
-                    self.map[y * self.width + x]
-                }).collect::<Vec<V>>()
-            }).flatten().collect()
-        } else {
-            (0..self.width).rev().map(|x| {
-                (0..self.height).map(|y| {
-                    self.map[y * self.width + x]
-                }).collect::<Vec<V>>()
-            }).flatten().collect()
-        };
-        self.map = new_map;
-        let width = self.width;
-        self.width = self.height;
-        self.height = width;        
+where V: FromCell
+{
+    /// Parses the grid from lines of text, every character is one cell.
+    pub fn parse(s: &str) -> Result<Self, ParseGridError> {
+        let mut map = vec![];
+        let mut width = 0;
+        let mut height = 0;
+        for (y, line) in s.lines().enumerate() {
+            let mut line_width = 0;
+            for (x, c) in line.chars().enumerate() {
+                let value = V::from_cell(c).ok_or(ParseGridError::UnknownCell { line: y + 1, column: x + 1, cell: c })?;
+                map.push(value);
+                line_width += 1;
+            }
+            if y == 0 {
+                width = line_width;
+            } else if line_width != width {
+                return Err(ParseGridError::RaggedRow { line: y + 1, width: line_width, expected: width });
+            }
+            height += 1;
+        }
+        if width == 0 {
+            return Err(ParseGridError::Empty);
+        }
+        Ok(Grid { map, height, width })
     }
 }
 
+impl<V> Grid<V>
+where V: ToCell
+{
+    /// Prints the grid in the same format [`Grid::parse`] reads.
+    pub fn to_cells(&self) -> String {
+        let mut s = String::with_capacity((self.width + 1) * self.height);
+        for row in self.map.chunks(self.width.max(1)) {
+            s.extend(row.iter().map(|v| v.to_cell()));
+            s.push('\n');
+        }
+        s
+    }
+}
 
 impl<V> Grid<V> 
 where 
     V: fmt::Display
 {
-    pub fn display_with_points(&self, points: Vec<Coord<usize>>, display_char: char) {
-        print!("\n");
-        for y in 0..self.height {
-            for x in 0..self.width {
-                let current = Coord::new(x, y);
-                if points.contains(&current) {
-                    print!("{}", display_char);
-                } else {
-                    let p = self.get_point(&current);
-                    print!("{}", p);
-                }
-            }
-            print!("\n");
-        }
+    /// Prints the grid with the points drawn as `display_char`, see
+    /// [`GridRenderer`] for more options.
+    pub fn display_with_points(&self, points: &[Coord<usize>], display_char: char) {
+        let renderer = GridRenderer::with_glyphs(self, |v| v.to_string())
+            .highlight(points, Some(display_char), Style::new());
+        print!("{}", renderer.render());
     }
 }
 
@@ -232,89 +279,62 @@ where
     V: fmt::Display
 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "\n")?;
-        for y in 0..self.height {            
+        for y in 0..self.height {
             for x in 0..self.width {
                 let v = self.get_val(&Coord::new(x, y));
-                    write!(f, "{}", v)?;
+                write!(f, "{}", v)?;
             }
-            write!(f, "\n")?;
+            writeln!(f)?;
         }
-        write!(f, "\n")
+        Ok(())
     }
 }
 
 
 
-// Endles iterator of the grid in specified direction
-// When it gets to the edge it jumps to the other side and 
-// continues iterating in that direction.
+// Iterator of the grid in specified direction, it stops at the edge of
+// the grid. The starting coordinate is not included.
 pub struct GridDirectionIterator {
     height: usize,
     width: usize,
-    direction: Direction,
+    step: (isize, isize),
     current_coord: Coord<usize>,
-    axis: Axis,
 }
 
 impl Iterator for GridDirectionIterator {
     type Item = Coord<usize>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let val = self.current_coord.get_mut(&self.axis);
-        match self.direction {
-            Direction::S => {
-                if *val == self.height - 1 { return None; } else { *val += 1; }
-            }
-            Direction::E => {
-                if *val == self.width - 1 { return None; } else { *val += 1; }
-            },
-            Direction::N => {
-                if *val == 0 { return None; } else { *val -= 1; }
-            }
-            Direction::W => {
-                if *val == 0 { return None; } else { *val -= 1; }
-            },
-            _ => unimplemented!("Iterator for direction {:?} is not implemented.", self.direction)
-        }        
-        Some(self.current_coord.clone())
+        let x = self.current_coord.x.checked_add_signed(self.step.0).filter(|x| *x < self.width)?;
+        let y = self.current_coord.y.checked_add_signed(self.step.1).filter(|y| *y < self.height)?;
+        self.current_coord = Coord::new(x, y);
+        Some(self.current_coord)
     }
 }
 
+// Endles iterator of the grid in specified direction
+// When it gets to the edge it jumps to the other side and
+// continues iterating in that direction.
 pub struct GridWrappedDirectionIterator {
     height: usize,
     width: usize,
-    direction: Direction,
+    step: (isize, isize),
     current_coord: Coord<usize>,
-    axis: Axis,
 }
 
 impl Iterator for GridWrappedDirectionIterator {
     type Item = Coord<usize>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let val = self.current_coord.get_mut(&self.axis);
-        match self.direction {
-            Direction::S => {
-                if *val == self.height - 1 { *val = 0; } else { *val += 1; }
-            }
-            Direction::E => {
-                if *val == self.width - 1 { *val = 0; } else { *val += 1; }
-            },
-            Direction::N => {
-                if *val == 0 { *val = self.height - 1; } else { *val -= 1; }
-            }
-            Direction::W => {
-                if *val == 0 { *val = self.width - 1; } else { *val -= 1; }
-            },
-            _ => unimplemented!("Iterator for direction {:?} is not implemented.", self.direction)
-        }        
-        Some(self.current_coord.clone())
+        let wrap = |v: usize, d: isize, len: usize| (v as isize + d).rem_euclid(len as isize) as usize;
+        self.current_coord = Coord::new(
+            wrap(self.current_coord.x, self.step.0, self.width),
+            wrap(self.current_coord.y, self.step.1, self.height),
+        );
+        Some(self.current_coord)
     }
 }
 
-
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -328,6 +348,32 @@ mod tests {
         Grid::new(map)
     }
 
+    #[test]
+    fn test_parse() {
+        let grid: Grid<u8> = Grid::parse("129\n340\r\n").unwrap();
+        assert_eq!((grid.width, grid.height), (3, 2));
+        assert_eq!(*grid.get_val(&Coord::new(2, 0)), 9);
+        assert_eq!(*grid.get_val(&Coord::new(2, 1)), 0);
+        assert_eq!(grid.map, vec![1, 2, 9, 3, 4, 0]);
+        assert_eq!(grid.to_cells(), "129\n340\n");
+
+        let empty: Grid<u8> = Grid { map: vec![], width: 0, height: 0 };
+        assert_eq!(empty.to_cells(), "");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(Grid::<u8>::parse("").unwrap_err(), ParseGridError::Empty);
+        assert_eq!(
+            Grid::<u8>::parse("129\n34").unwrap_err(),
+            ParseGridError::RaggedRow { line: 2, width: 2, expected: 3 }
+        );
+        assert_eq!(
+            Grid::<u8>::parse("129\n3x0").unwrap_err(),
+            ParseGridError::UnknownCell { line: 2, column: 2, cell: 'x' }
+        );
+    }
+
      #[test]
     fn test_contains() {
         let grid = construct_grid();
@@ -339,4 +385,31 @@ mod tests {
         let c: Coord<usize> = Coord::new(1, 1);
         assert_eq!(grid.contains(&c), true);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_direction_iterators() {
+        let grid: Grid<u8> = Grid::parse("012\n345\n678").unwrap();
+        let ray = |direction, x, y| grid.ray(direction, Coord::new(x, y)).map(|p| *p.value).collect::<Vec<_>>();
+        assert_eq!(ray(Direction::E, 0, 0), vec![1, 2]);
+        assert_eq!(ray(Direction::N, 1, 2), vec![4, 1]);
+        assert_eq!(ray(Direction::SE, 0, 0), vec![4, 8]);
+        assert_eq!(ray(Direction::NE, 0, 2), vec![4, 2]);
+        assert_eq!(ray(Direction::SW, 2, 1), vec![7]);
+        assert_eq!(ray(Direction::NW, 0, 1), vec![]);
+
+        let knight: Vec<_> = grid.step_iter((1, 2), Coord::new(0, 0)).collect();
+        assert_eq!(knight, vec![Coord::new(1, 2)]);
+
+        let wrapped: Vec<_> = grid.wrapped_ray(Direction::NW, Coord::new(0, 0)).take(4).map(|p| *p.value).collect();
+        assert_eq!(wrapped, vec![8, 4, 0, 8]);
+        let wrapped: Vec<_> = grid.wrapped_step_iter((-4, 5), Coord::new(0, 0)).take(2).collect();
+        assert_eq!(wrapped, vec![Coord::new(2, 2), Coord::new(1, 1)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "never leaves")]
+    fn test_zero_step() {
+        let grid: Grid<u8> = Grid::parse("01\n23").unwrap();
+        grid.step_iter((0, 0), Coord::new(1, 1));
+    }
+}
@@ -0,0 +1,224 @@
+use std::fmt::Write as _;
+use std::io;
+
+use funty::Integral;
+
+use super::coord::Coord;
+use super::endless_grid::Grid as EndlessGrid;
+use super::grid::Grid;
+
+impl<V> Grid<V> {
+    /// Writes the grid as a binary PPM (P6) image, every cell is a
+    /// `scale` x `scale` square of the color.
+    pub fn write_ppm(&self, writer: &mut impl io::Write, scale: usize, color: impl Fn(&V) -> [u8; 3]) -> io::Result<()> {
+        write!(writer, "P6\n{} {}\n255\n", self.width * scale, self.height * scale)?;
+        self.write_pixels(writer, scale, |v| color(v).to_vec())
+    }
+
+    /// Writes the grid as a binary PGM (P5) grayscale image.
+    pub fn write_pgm(&self, writer: &mut impl io::Write, scale: usize, gray: impl Fn(&V) -> u8) -> io::Result<()> {
+        write!(writer, "P5\n{} {}\n255\n", self.width * scale, self.height * scale)?;
+        self.write_pixels(writer, scale, |v| vec![gray(v)])
+    }
+
+    fn write_pixels(&self, writer: &mut impl io::Write, scale: usize, pixel: impl Fn(&V) -> Vec<u8>) -> io::Result<()> {
+        let mut line = vec![];
+        for y in 0..self.height {
+            line.clear();
+            for x in 0..self.width {
+                let pixel = pixel(self.get_val(&Coord::new(x, y)));
+                for _ in 0..scale {
+                    line.extend_from_slice(&pixel);
+                }
+            }
+            for _ in 0..scale {
+                writer.write_all(&line)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<K, V> EndlessGrid<K, V>
+where
+    K: Integral,
+    V: Clone
+{
+    /// PPM image of the bounding box, unset cells have the default's color.
+    pub fn write_ppm(&self, writer: &mut impl io::Write, scale: usize, color: impl Fn(&V) -> [u8; 3]) -> io::Result<()> {
+        match self.to_dense() {
+            Some((_, grid)) => grid.write_ppm(writer, scale, color),
+            None => write!(writer, "P6\n0 0\n255\n"),
+        }
+    }
+
+    pub fn write_pgm(&self, writer: &mut impl io::Write, scale: usize, gray: impl Fn(&V) -> u8) -> io::Result<()> {
+        match self.to_dense() {
+            Some((_, grid)) => grid.write_pgm(writer, scale, gray),
+            None => write!(writer, "P5\n0 0\n255\n"),
+        }
+    }
+}
+
+enum Shape {
+    Points { points: Vec<(i64, i64)>, radius: f64, color: String },
+    Polyline { points: Vec<(i64, i64)>, closed: bool, stroke: String },
+    Rect { min: (i64, i64), max: (i64, i64), fill: String },
+}
+
+/// SVG drawing of points, polylines and rectangles in grid coordinates
+/// (y grows downwards). The view box fits all the shapes, the strokes are
+/// sized relative to it so huge coordinates stay visible.
+pub struct Svg {
+    shapes: Vec<Shape>,
+    width: usize,
+}
+
+impl Svg {
+    pub fn new() -> Self {
+        Svg { shapes: vec![], width: 800 }
+    }
+
+    /// Width of the image in pixels, the height follows the aspect ratio.
+    pub fn width(mut self, width: usize) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Dots with the radius relative to the view box size.
+    pub fn points<T: Integral>(mut self, points: &[Coord<T>], radius: f64, color: &str) -> Self {
+        let points = points.iter().map(to_i64).collect();
+        self.shapes.push(Shape::Points { points, radius, color: color.to_owned() });
+        self
+    }
+
+    pub fn polyline<T: Integral>(mut self, points: &[Coord<T>], closed: bool, stroke: &str) -> Self {
+        let points = points.iter().map(to_i64).collect();
+        self.shapes.push(Shape::Polyline { points, closed, stroke: stroke.to_owned() });
+        self
+    }
+
+    /// Rectangle between the corners, both inclusive like grid cells.
+    pub fn rect<T: Integral>(mut self, a: &Coord<T>, b: &Coord<T>, fill: &str) -> Self {
+        let (a, b) = (to_i64(a), to_i64(b));
+        let min = (a.0.min(b.0), a.1.min(b.1));
+        let max = (a.0.max(b.0), a.1.max(b.1));
+        self.shapes.push(Shape::Rect { min, max, fill: fill.to_owned() });
+        self
+    }
+
+    fn view_box(&self) -> (i64, i64, i64, i64) {
+        let corners = self.shapes.iter().flat_map(|shape| match shape {
+            Shape::Points { points, .. } | Shape::Polyline { points, .. } => points.clone(),
+            Shape::Rect { min, max, .. } => vec![*min, (max.0 + 1, max.1 + 1)],
+        });
+        let (min_x, min_y, max_x, max_y) = corners.fold(
+            (i64::MAX, i64::MAX, i64::MIN, i64::MIN),
+            |(a, b, c, d), (x, y)| (a.min(x), b.min(y), c.max(x), d.max(y))
+        );
+        if min_x > max_x {
+            return (0, 0, 1, 1);
+        }
+        let margin = ((max_x - min_x).max(max_y - min_y) / 50).max(1);
+        (min_x - margin, min_y - margin, max_x - min_x + 2 * margin, max_y - min_y + 2 * margin)
+    }
+
+    pub fn render(&self) -> String {
+        let (x, y, w, h) = self.view_box();
+        let unit = w.max(h) as f64 / 500.0;
+        let height = (self.width as f64 * h as f64 / w as f64).round() as usize;
+        let mut svg = String::new();
+        let _ = writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="{} {} {} {}">"#,
+            self.width, height, x, y, w, h
+        );
+        for shape in &self.shapes {
+            let _ = match shape {
+                Shape::Points { points, radius, color } => points.iter().try_for_each(|(px, py)| {
+                    writeln!(svg, r#"  <circle cx="{}" cy="{}" r="{:.3}" fill="{}"/>"#, px, py, radius * unit, color)
+                }),
+                Shape::Polyline { points, closed, stroke } => {
+                    let element = if *closed { "polygon" } else { "polyline" };
+                    let points: Vec<String> = points.iter().map(|(px, py)| format!("{},{}", px, py)).collect();
+                    writeln!(
+                        svg,
+                        r#"  <{} points="{}" fill="none" stroke="{}" stroke-width="{:.3}"/>"#,
+                        element, points.join(" "), stroke, unit
+                    )
+                },
+                Shape::Rect { min, max, fill } => writeln!(
+                    svg,
+                    r#"  <rect x="{}" y="{}" width="{}" height="{}" fill="{}" fill-opacity="0.5"/>"#,
+                    min.0, min.1, max.0 - min.0 + 1, max.1 - min.1 + 1, fill
+                ),
+            };
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    pub fn write_to(&self, writer: &mut impl io::Write) -> io::Result<()> {
+        writer.write_all(self.render().as_bytes())
+    }
+}
+
+impl Default for Svg {
+    fn default() -> Self {
+        Svg::new()
+    }
+}
+
+fn to_i64<T: Integral>(coord: &Coord<T>) -> (i64, i64) {
+    (coord.x.as_i64(), coord.y.as_i64())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ppm_and_pgm() {
+        let grid: Grid<char> = Grid::parse("#.\n.#").unwrap();
+        let mut ppm = vec![];
+        grid.write_ppm(&mut ppm, 2, |c| if *c == '#' { [255, 0, 0] } else { [0, 0, 0] }).unwrap();
+        let header = b"P6\n4 4\n255\n";
+        assert_eq!(&ppm[..header.len()], header);
+        assert_eq!(ppm.len(), header.len() + 4 * 4 * 3);
+        // Second row of pixels is the first row of cells again
+        assert_eq!(&ppm[header.len()..header.len() + 12], &ppm[header.len() + 12..header.len() + 24]);
+        assert_eq!(&ppm[header.len()..header.len() + 12], &[255, 0, 0, 255, 0, 0, 0, 0, 0, 0, 0, 0]);
+
+        let mut endless: EndlessGrid<i32, u8> = EndlessGrid::new(0);
+        endless.insert(Coord::new(-1, 5), 200);
+        endless.insert(Coord::new(0, 6), 100);
+        let mut pgm = vec![];
+        endless.write_pgm(&mut pgm, 1, |v| *v).unwrap();
+        assert_eq!(pgm, b"P5\n2 2\n255\n\xc8\x00\x00\x64".to_vec());
+    }
+
+    #[test]
+    fn test_svg_snapshot() {
+        let polygon: Vec<Coord<usize>> = vec![
+            Coord::new(7, 1), Coord::new(11, 1), Coord::new(11, 7), Coord::new(9, 7),
+            Coord::new(9, 5), Coord::new(2, 5), Coord::new(2, 3), Coord::new(7, 3),
+        ];
+        let svg = Svg::new()
+            .width(100)
+            .polyline(&polygon, true, "green")
+            .rect(&Coord::new(9, 5), &Coord::new(2, 3), "red")
+            .points(&polygon[..1], 2.0, "black")
+            .render();
+        assert_eq!(svg, r#"<svg xmlns="http://www.w3.org/2000/svg" width="100" height="73" viewBox="1 0 11 8">
+  <polygon points="7,1 11,1 11,7 9,7 9,5 2,5 2,3 7,3" fill="none" stroke="green" stroke-width="0.022"/>
+  <rect x="2" y="3" width="8" height="3" fill="red" fill-opacity="0.5"/>
+  <circle cx="7" cy="1" r="0.044" fill="black"/>
+</svg>
+"#);
+        assert_eq!(svg, Svg::new().width(100)
+            .polyline(&polygon, true, "green")
+            .rect(&Coord::new(9, 5), &Coord::new(2, 3), "red")
+            .points(&polygon[..1], 2.0, "black")
+            .render());
+    }
+}
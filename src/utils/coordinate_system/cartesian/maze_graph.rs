@@ -0,0 +1,336 @@
+use std::collections::{HashMap, HashSet};
+
+use owo_colors::Style;
+
+use super::cell::ToCell;
+use super::coord::Coord;
+use super::grid::Grid;
+use super::neighbourhood::Neighbourhood;
+use super::pathfinding::{best_first, DistanceMap};
+use super::point::Point;
+use super::render::GridRenderer;
+
+type OpenFn<'a, V> = Box<dyn Fn(&V) -> bool + 'a>;
+type StepFn<'a, V> = Box<dyn Fn(&Point<usize, &V>, &Point<usize, &V>) -> bool + 'a>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NodeKind {
+    /// Open cell with three or more open neighbours
+    Junction,
+    /// Open cell with at most one open neighbour
+    DeadEnd,
+    PointOfInterest,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MazeNode {
+    pub coord: Coord<usize>,
+    pub kind: NodeKind,
+}
+
+/// Corridor from a node to another one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Edge {
+    pub to: usize,
+    /// Number of steps, one more than the cells between the nodes
+    pub length: usize,
+    /// Cells between the nodes, only kept when asked for
+    pub path: Option<Vec<Coord<usize>>>,
+}
+
+/// Builds a [`MazeGraph`] from the open cells of a grid. Corridors follow
+/// the touching neighbourhood, `step(from, to)` can make some steps one-way
+/// (e.g. slopes) and then the corridor only gets an edge in the allowed
+/// direction.
+pub struct MazeGraphBuilder<'a, V> {
+    grid: &'a Grid<V>,
+    open: OpenFn<'a, V>,
+    step: StepFn<'a, V>,
+    points_of_interest: HashSet<Coord<usize>>,
+    keep_paths: bool,
+}
+
+impl<'a, V> MazeGraphBuilder<'a, V> {
+    pub fn new(grid: &'a Grid<V>, open: impl Fn(&V) -> bool + 'a) -> Self {
+        MazeGraphBuilder {
+            grid,
+            open: Box::new(open),
+            step: Box::new(|_, _| true),
+            points_of_interest: HashSet::new(),
+            keep_paths: false,
+        }
+    }
+
+    pub fn step(mut self, step: impl Fn(&Point<usize, &V>, &Point<usize, &V>) -> bool + 'a) -> Self {
+        self.step = Box::new(step);
+        self
+    }
+
+    /// Cells that are always nodes, like the start and the end.
+    pub fn points_of_interest<'c>(mut self, cells: impl IntoIterator<Item = &'c Coord<usize>>) -> Self {
+        self.points_of_interest.extend(cells);
+        self
+    }
+
+    pub fn keep_paths(mut self, keep_paths: bool) -> Self {
+        self.keep_paths = keep_paths;
+        self
+    }
+
+    fn is_open(&self, coord: &Coord<usize>) -> bool {
+        (self.open)(self.grid.get_val(coord))
+    }
+
+    fn open_neighbours(&self, coord: Coord<usize>) -> impl Iterator<Item = Coord<usize>> + '_ {
+        self.grid.neighbours(coord, Neighbourhood::Touching).filter(|c| self.is_open(c))
+    }
+
+    pub fn build(&self) -> MazeGraph {
+        let mut nodes = vec![];
+        for coord in self.grid.iter_coords().filter(|c| self.is_open(c)) {
+            let kind = if self.points_of_interest.contains(&coord) {
+                NodeKind::PointOfInterest
+            } else {
+                match self.open_neighbours(coord).count() {
+                    0 | 1 => NodeKind::DeadEnd,
+                    2 => continue,
+                    _ => NodeKind::Junction,
+                }
+            };
+            nodes.push(MazeNode { coord, kind });
+        }
+        let index: HashMap<Coord<usize>, usize> = nodes.iter().enumerate().map(|(i, n)| (n.coord, i)).collect();
+
+        let edges = nodes.iter().map(|node| {
+            self.open_neighbours(node.coord)
+                .filter_map(|first| self.follow(node.coord, first, &index))
+                .collect()
+        }).collect();
+        MazeGraph { nodes, edges, index, width: self.grid.width, height: self.grid.height }
+    }
+
+    /// Walks the corridor from the node through `first` to the next node.
+    fn follow(&self, node: Coord<usize>, first: Coord<usize>, index: &HashMap<Coord<usize>, usize>) -> Option<Edge> {
+        let allowed = |from: &Coord<usize>, to: &Coord<usize>| {
+            (self.step)(&self.grid.get_point(from), &self.grid.get_point(to))
+        };
+        if !allowed(&node, &first) {
+            return None;
+        }
+        let mut path = vec![];
+        let (mut previous, mut current) = (node, first);
+        let mut length = 1;
+        loop {
+            if let Some(to) = index.get(&current) {
+                let path = self.keep_paths.then_some(path);
+                return Some(Edge { to: *to, length, path });
+            }
+            path.push(current);
+            // Not a node, so exactly two open neighbours
+            let next = self.open_neighbours(current).find(|c| *c != previous)?;
+            if !allowed(&current, &next) {
+                return None;
+            }
+            (previous, current) = (current, next);
+            length += 1;
+        }
+    }
+}
+
+/// Junctions, dead ends and points of interest of a maze connected by the
+/// corridors between them.
+#[derive(Debug, Clone)]
+pub struct MazeGraph {
+    pub nodes: Vec<MazeNode>,
+    /// Outgoing edges of every node
+    pub edges: Vec<Vec<Edge>>,
+    index: HashMap<Coord<usize>, usize>,
+    /// Size of the maze, the searches keep their distances by cell
+    width: usize,
+    height: usize,
+}
+
+impl MazeGraph {
+    pub fn node(&self, coord: &Coord<usize>) -> Option<usize> {
+        self.index.get(coord).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Dijkstra from the node, the distance to every node it reaches.
+    pub fn distances(&self, from: usize) -> Vec<Option<usize>> {
+        let map = self.dijkstra(from);
+        self.nodes.iter().map(|node| map.distance(&node.coord)).collect()
+    }
+
+    /// Length of the shortest path and it's nodes, both ends included.
+    pub fn shortest_path(&self, from: usize, to: usize) -> Option<(usize, Vec<usize>)> {
+        let map = self.dijkstra(from);
+        let target = self.nodes[to].coord;
+        let path = map.path_to(&target)?;
+        Some((map.distance(&target)?, path.iter().map(|c| self.index[c]).collect()))
+    }
+
+    /// Same search as [`PathFinder::dijkstra`] on the maze, stepping along
+    /// the corridors instead of cell by cell. Only the nodes are reached.
+    ///
+    /// [`PathFinder::dijkstra`]: super::PathFinder::dijkstra
+    pub fn dijkstra(&self, from: usize) -> DistanceMap {
+        let steps = |coord: Coord<usize>| {
+            self.edges[self.index[&coord]].iter().map(|edge| (self.nodes[edge.to].coord, edge.length))
+        };
+        best_first(self.width, self.height, &[self.nodes[from].coord], steps, |_| false, |_| 0)
+    }
+
+    /// Longest path that never visits a node twice. Tries every such path,
+    /// so it's only usable because the graph is much smaller than the maze.
+    pub fn longest_path(&self, from: usize, to: usize) -> Option<usize> {
+        let mut visited = vec![false; self.nodes.len()];
+        self.longest_from(from, to, &mut visited)
+    }
+
+    fn longest_from(&self, node: usize, to: usize, visited: &mut [bool]) -> Option<usize> {
+        if node == to {
+            return Some(0);
+        }
+        visited[node] = true;
+        let mut longest = None;
+        for edge in &self.edges[node] {
+            if visited[edge.to] {
+                continue;
+            }
+            if let Some(rest) = self.longest_from(edge.to, to, visited) {
+                longest = longest.max(Some(edge.length + rest));
+            }
+        }
+        visited[node] = false;
+        longest
+    }
+
+    /// Cells of a path of nodes, taking the shortest corridor between every
+    /// two nodes. `None` when the paths were not kept or two nodes are not
+    /// connected.
+    pub fn cells(&self, nodes: &[usize]) -> Option<Vec<Coord<usize>>> {
+        let mut cells = vec![self.nodes[*nodes.first()?].coord];
+        for pair in nodes.windows(2) {
+            let edge = self.edges[pair[0]].iter()
+                .filter(|edge| edge.to == pair[1])
+                .min_by_key(|edge| edge.length)?;
+            cells.extend(edge.path.as_ref()?);
+            cells.push(self.nodes[pair[1]].coord);
+        }
+        Some(cells)
+    }
+
+    /// Renderer of the grid with the corridors and nodes drawn over it.
+    pub fn renderer<'a, V: ToCell>(&self, grid: &'a Grid<V>) -> GridRenderer<'a, V> {
+        let corridors: Vec<Coord<usize>> = self.edges.iter().flatten()
+            .flat_map(|edge| edge.path.iter().flatten().copied())
+            .collect();
+        let of_kind = |kind: NodeKind| -> Vec<Coord<usize>> {
+            self.nodes.iter().filter(|n| n.kind == kind).map(|n| n.coord).collect()
+        };
+        GridRenderer::new(grid)
+            .highlight(&corridors, Some('·'), Style::new().dimmed())
+            .highlight(&of_kind(NodeKind::Junction), Some('+'), Style::new().yellow())
+            .highlight(&of_kind(NodeKind::DeadEnd), Some('x'), Style::new().red())
+            .highlight(&of_kind(NodeKind::PointOfInterest), None, Style::new().green().bold())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::pathfinding::PathFinder;
+
+    fn maze() -> Grid<char> {
+        Grid::parse("\
+#S#####
+#.....#
+#.###.#
+#.#...#
+#.#.#.#
+#...#.#
+#####E#").unwrap()
+    }
+
+    fn build(grid: &Grid<char>, keep_paths: bool) -> MazeGraph {
+        MazeGraphBuilder::new(grid, |c| *c != '#')
+            .points_of_interest(&[Coord::new(1, 0), Coord::new(5, 6)])
+            .keep_paths(keep_paths)
+            .build()
+    }
+
+    #[test]
+    fn test_nodes_and_edges() {
+        let grid = maze();
+        let graph = build(&grid, false);
+        let kinds: Vec<_> = graph.nodes.iter().map(|n| (n.coord, n.kind)).collect();
+        assert_eq!(kinds, vec![
+            (Coord::new(1, 0), NodeKind::PointOfInterest),
+            (Coord::new(1, 1), NodeKind::Junction),
+            (Coord::new(5, 3), NodeKind::Junction),
+            (Coord::new(5, 6), NodeKind::PointOfInterest),
+        ]);
+        let junction = graph.node(&Coord::new(1, 1)).unwrap();
+        let mut lengths: Vec<_> = graph.edges[junction].iter().map(|e| (e.to, e.length)).collect();
+        lengths.sort();
+        assert_eq!(lengths, vec![(0, 1), (2, 6), (2, 10)]);
+        assert!(graph.edges.iter().flatten().all(|e| e.path.is_none()));
+    }
+
+    #[test]
+    fn test_searches() {
+        let grid = maze();
+        let graph = build(&grid, true);
+        let (start, end) = (graph.node(&Coord::new(1, 0)).unwrap(), graph.node(&Coord::new(5, 6)).unwrap());
+        let (distance, nodes) = graph.shortest_path(start, end).unwrap();
+        assert_eq!(nodes, vec![0, 1, 2, 3]);
+
+        let finder = PathFinder::new(&grid, Neighbourhood::Touching).passable(|_, to| *to.value != '#');
+        let map = finder.bfs(&[Coord::new(1, 0)], |_| false);
+        assert_eq!(Some(distance), map.distance(&Coord::new(5, 6)));
+        assert_eq!(graph.distances(start)[end], Some(distance));
+        let nodes_only = graph.dijkstra(start);
+        assert_eq!(nodes_only.distance(&Coord::new(5, 6)), Some(distance));
+        assert_eq!(nodes_only.distance(&Coord::new(3, 1)), None);
+        assert_eq!(graph.longest_path(start, end), Some(1 + 10 + 3));
+
+        let cells = graph.cells(&nodes).unwrap();
+        assert_eq!(cells.len(), distance + 1);
+        assert!(cells.windows(2).all(|w| w[0].x.abs_diff(w[1].x) + w[0].y.abs_diff(w[1].y) == 1));
+        assert_eq!(build(&grid, false).cells(&nodes), None);
+    }
+
+    #[test]
+    fn test_one_way_steps() {
+        // The slope can only be walked down
+        let grid: Grid<char> = Grid::parse("\
+#S#
+#v#
+#.#
+#E#").unwrap();
+        let graph = MazeGraphBuilder::new(&grid, |c| *c != '#')
+            .step(|from, to| to.coord.y > from.coord.y || (*from.value != 'v' && *to.value != 'v'))
+            .build();
+        let (start, end) = (graph.node(&Coord::new(1, 0)).unwrap(), graph.node(&Coord::new(1, 3)).unwrap());
+        assert_eq!(graph.shortest_path(start, end).map(|(d, _)| d), Some(3));
+        assert_eq!(graph.shortest_path(end, start), None);
+    }
+
+    #[test]
+    fn test_render() {
+        let grid = maze();
+        let graph = build(&grid, true);
+        let rendered = graph.renderer(&grid).render();
+        assert_eq!(rendered.lines().nth(1), Some("#+····#"));
+        assert_eq!(rendered.lines().nth(3), Some("#·#··+#"));
+        assert_eq!(rendered.lines().last(), Some("#####E#"));
+    }
+}
@@ -1,13 +1,46 @@
+mod automaton;
 mod axis;
+mod bit_grid;
+mod cell;
 mod coord;
 mod direction;
 mod grid;
+mod image;
+mod maze_graph;
+mod neighbourhood;
+mod pathfinding;
+mod pattern;
+mod offset_grid;
+mod orientation;
 mod point;
+mod prefix_sums;
+mod regions;
+mod render;
+mod resize;
+mod sight;
+mod transform;
+mod views;
 mod endless_grid;
 
+pub use automaton::{Automaton, Neighbours};
 pub use axis::Axis;
+pub use bit_grid::{BitGrid, NeighbourCounts};
+pub use cell::{FromCell, ToCell, ParseGridError};
 pub use coord::Coord;
 pub use direction::{Direction, DIRECTIONS, TOUCHING_DIRECTIONS};
+pub use image::Svg;
 pub use grid::{Grid, GridDirectionIterator, GridWrappedDirectionIterator};
+pub use maze_graph::{Edge, MazeGraph, MazeGraphBuilder, MazeNode, NodeKind};
+pub use neighbourhood::{Neighbourhood, NeighbourOffsets};
+pub use pathfinding::{DistanceMap, PathFinder};
+pub use offset_grid::OffsetGrid;
+pub use orientation::{Orientation, Oriented, YAxis, YDown, YUp};
 pub use point::Point;
+pub use prefix_sums::{Compressed, CompressedAxis, DifferenceArray, SummedArea};
+pub use regions::{Region, Regions};
+pub use render::GridRenderer;
+pub use resize::{Bordered, Tiled};
+pub use sight::{Ray, RayIterator};
+pub use transform::{Symmetry, SYMMETRIES};
+pub use views::{SubGrid, SubGridMut};
 pub use endless_grid::Grid as EndlessGrid;
\ No newline at end of file
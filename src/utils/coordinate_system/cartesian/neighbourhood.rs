@@ -0,0 +1,151 @@
+use super::coord::Coord;
+use super::direction::{Direction, DIRECTIONS, TOUCHING_DIRECTIONS};
+use super::grid::Grid;
+use super::orientation::Oriented;
+
+/// Which cells count as neighbours of a cell. Wrapping neighbourhoods
+/// continue on the opposite edge of the grid instead of stopping at it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Neighbourhood {
+    /// N, E, S, W
+    Touching,
+    /// All eight directions
+    Adjacent,
+    TouchingWrapping,
+    AdjacentWrapping,
+}
+
+impl Neighbourhood {
+    pub fn directions(&self) -> &'static [Direction] {
+        match self {
+            Neighbourhood::Touching | Neighbourhood::TouchingWrapping => &TOUCHING_DIRECTIONS,
+            Neighbourhood::Adjacent | Neighbourhood::AdjacentWrapping => &DIRECTIONS,
+        }
+    }
+
+    pub fn wrapping(&self) -> bool {
+        matches!(self, Neighbourhood::TouchingWrapping | Neighbourhood::AdjacentWrapping)
+    }
+}
+
+impl<V> Grid<V> {
+    /// Neighbours of the coordinate in the order of the neighbourhood's
+    /// directions.
+    pub fn neighbours(&self, coord: Coord<usize>, neighbourhood: Neighbourhood) -> impl Iterator<Item = Coord<usize>> + '_ {
+        neighbourhood.directions().iter().filter_map(move |direction| {
+            if neighbourhood.wrapping() {
+                Some(self.get_neighbour_wrapping(&coord, direction))
+            } else {
+                self.get_neighbour(&coord, direction)
+            }
+        })
+    }
+
+    /// Neighbours indexed by the position of the direction in
+    /// [`DIRECTIONS`], directions outside of the neighbourhood are `None`.
+    pub fn neighbour_array(&self, coord: &Coord<usize>, neighbourhood: Neighbourhood) -> [Option<Coord<usize>>; 8] {
+        let mut neighbours = [None; 8];
+        for (i, direction) in DIRECTIONS.iter().enumerate() {
+            if neighbourhood.directions().contains(direction) {
+                neighbours[i] = if neighbourhood.wrapping() {
+                    Some(self.get_neighbour_wrapping(coord, direction))
+                } else {
+                    self.get_neighbour(coord, direction)
+                };
+            }
+        }
+        neighbours
+    }
+
+    /// Offset table of the neighbourhood over the indices of `map`.
+    pub fn neighbour_offsets(&self, neighbourhood: Neighbourhood) -> NeighbourOffsets {
+        let mut offsets = [0; 8];
+        let mut steps = [(0, 0); 8];
+        for (i, direction) in neighbourhood.directions().iter().enumerate() {
+            let step: Coord<isize> = Grid::<V>::unit_step(direction);
+            steps[i] = (step.x, step.y);
+            offsets[i] = step.y * self.width as isize + step.x;
+        }
+        NeighbourOffsets {
+            offsets,
+            steps,
+            len: neighbourhood.directions().len(),
+            width: self.width,
+            height: self.height,
+            wrapping: neighbourhood.wrapping(),
+        }
+    }
+}
+
+/// Neighbours of cells by their index in the grid's `map`. Cells away from
+/// the edges only add the precomputed offsets to the index.
+#[derive(Debug, Clone, Copy)]
+pub struct NeighbourOffsets {
+    offsets: [isize; 8],
+    steps: [(isize, isize); 8],
+    len: usize,
+    width: usize,
+    height: usize,
+    wrapping: bool,
+}
+
+impl NeighbourOffsets {
+    pub fn neighbours(&self, index: usize) -> impl Iterator<Item = usize> + '_ {
+        let (x, y) = (index % self.width, index / self.width);
+        let inner = x > 0 && y > 0 && x + 1 < self.width && y + 1 < self.height;
+        (0..self.len).filter_map(move |i| {
+            if inner {
+                return Some(index.wrapping_add_signed(self.offsets[i]));
+            }
+            let (dx, dy) = self.steps[i];
+            let (nx, ny) = (x as isize + dx, y as isize + dy);
+            let (w, h) = (self.width as isize, self.height as isize);
+            if self.wrapping {
+                Some((ny.rem_euclid(h) * w + nx.rem_euclid(w)) as usize)
+            } else if nx < 0 || ny < 0 || nx >= w || ny >= h {
+                None
+            } else {
+                Some((ny * w + nx) as usize)
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_neighbours() {
+        let grid = Grid::new(vec![vec![0; 3]; 3]);
+        let corner: Vec<_> = grid.neighbours(Coord::new(0, 0), Neighbourhood::Touching).collect();
+        assert_eq!(corner, vec![Coord::new(1, 0), Coord::new(0, 1)]);
+        let corner: Vec<_> = grid.neighbours(Coord::new(0, 0), Neighbourhood::Adjacent).collect();
+        assert_eq!(corner, vec![Coord::new(1, 0), Coord::new(1, 1), Coord::new(0, 1)]);
+        let corner: Vec<_> = grid.neighbours(Coord::new(0, 0), Neighbourhood::AdjacentWrapping).collect();
+        assert_eq!(corner.len(), 8);
+        assert_eq!(corner[0], Coord::new(0, 2));
+        assert_eq!(corner[7], Coord::new(2, 2));
+    }
+
+    #[test]
+    fn test_neighbour_array_and_offsets() {
+        let grid = Grid::new(vec![vec![0; 4]; 3]);
+        let array = grid.neighbour_array(&Coord::new(0, 0), Neighbourhood::Touching);
+        assert_eq!(array, [None, None, Some(Coord::new(1, 0)), None, Some(Coord::new(0, 1)), None, None, None]);
+
+        for neighbourhood in [
+            Neighbourhood::Touching, Neighbourhood::Adjacent,
+            Neighbourhood::TouchingWrapping, Neighbourhood::AdjacentWrapping,
+        ] {
+            let offsets = grid.neighbour_offsets(neighbourhood);
+            for coord in grid.iter_coords() {
+                let by_index: Vec<_> = offsets.neighbours(grid.get_index(&coord)).collect();
+                let by_coord: Vec<_> = grid.neighbours(coord, neighbourhood).map(|c| grid.get_index(&c)).collect();
+                assert_eq!(by_index, by_coord, "{:?} {:?}", neighbourhood, coord);
+                let from_array: Vec<_> = grid.neighbour_array(&coord, neighbourhood).into_iter().flatten().collect();
+                assert_eq!(from_array.len(), by_coord.len());
+            }
+        }
+    }
+}
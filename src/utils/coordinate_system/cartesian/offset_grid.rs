@@ -0,0 +1,237 @@
+use std::fmt;
+
+use funty::Signed;
+
+use super::coord::Coord;
+use super::grid::Grid;
+use super::neighbourhood::Neighbourhood;
+use super::orientation::{Oriented, YDown};
+use super::point::Point;
+
+/// Dense grid indexed by signed coordinates. The top left cell is at
+/// `origin`, cells left of or above it are outside until the grid grows
+/// over them.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct OffsetGrid<K, V> {
+    grid: Grid<V>,
+    origin: Coord<K>,
+    /// Value of the new cells when the grid grows
+    default: V,
+}
+
+/// Same orientation as [`Grid`], the origin is the top left cell.
+impl<K, V> Oriented for OffsetGrid<K, V> {
+    type Orientation = YDown;
+}
+
+impl<K, V> OffsetGrid<K, V>
+where K: Signed
+{
+    /// Empty grid, the first cell that is grown to sets the origin.
+    pub fn new(default: V) -> Self {
+        Self::from_grid(Grid { map: vec![], width: 0, height: 0 }, Coord::new(K::ZERO, K::ZERO), default)
+    }
+
+    /// Grid with it's top left cell at `origin`.
+    pub fn from_grid(grid: Grid<V>, origin: Coord<K>, default: V) -> Self {
+        OffsetGrid { grid, origin, default }
+    }
+
+    pub fn origin(&self) -> Coord<K> {
+        self.origin
+    }
+
+    pub fn width(&self) -> usize {
+        self.grid.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.grid.height
+    }
+
+    /// Top left and bottom right cells, `None` for an empty grid.
+    pub fn bounds(&self) -> Option<(Coord<K>, Coord<K>)> {
+        if self.grid.map.is_empty() {
+            return None;
+        }
+        Some((self.origin, self.to_global(&Coord::new(self.grid.width - 1, self.grid.height - 1))))
+    }
+
+    /// The dense grid, it's `(0, 0)` is the origin.
+    pub fn grid(&self) -> &Grid<V> {
+        &self.grid
+    }
+
+    pub fn into_grid(self) -> (Coord<K>, Grid<V>) {
+        (self.origin, self.grid)
+    }
+
+    /// Coordinate in the dense grid, `None` outside of it.
+    pub fn to_local(&self, coord: &Coord<K>) -> Option<Coord<usize>> {
+        let (x, y) = (coord.x - self.origin.x, coord.y - self.origin.y);
+        if x < K::ZERO || y < K::ZERO || x.as_usize() >= self.grid.width || y.as_usize() >= self.grid.height {
+            return None;
+        }
+        Some(Coord::new(x.as_usize(), y.as_usize()))
+    }
+
+    pub fn to_global(&self, coord: &Coord<usize>) -> Coord<K> {
+        let offset = |v: usize| K::try_from(v).unwrap_or_else(|_| panic!("Coordinate {} does not fit the key type", v));
+        Coord::new(self.origin.x + offset(coord.x), self.origin.y + offset(coord.y))
+    }
+
+    pub fn contains(&self, coord: &Coord<K>) -> bool {
+        self.to_local(coord).is_some()
+    }
+
+    pub fn get(&self, coord: &Coord<K>) -> Option<&V> {
+        self.to_local(coord).map(|c| self.grid.get_val(&c))
+    }
+
+    /// Value of the cell, the default outside of the grid.
+    pub fn get_or_default(&self, coord: &Coord<K>) -> &V {
+        self.get(coord).unwrap_or(&self.default)
+    }
+
+    pub fn get_mut(&mut self, coord: &Coord<K>) -> Option<&mut V> {
+        let local = self.to_local(coord)?;
+        Some(self.grid.get_val_mut(&local))
+    }
+
+    /// Neighbours inside the grid in the order of the neighbourhood's
+    /// directions. Wrapping neighbourhoods wrap around the current bounds.
+    pub fn neighbours(&self, coord: &Coord<K>, neighbourhood: Neighbourhood) -> impl Iterator<Item = Coord<K>> + '_ {
+        self.to_local(coord)
+            .into_iter()
+            .flat_map(move |local| self.grid.neighbours(local, neighbourhood))
+            .map(|c| self.to_global(&c))
+    }
+
+    pub fn iter_points(&self) -> impl Iterator<Item = Point<K, &V>> + '_ {
+        self.grid.iter_points().map(|p| Point::from_coord(self.to_global(&p.coord), p.value))
+    }
+}
+
+impl<K, V> OffsetGrid<K, V>
+where
+    K: Signed,
+    V: Clone
+{
+    /// Grows the grid until it contains the cell. It grows by at least half
+    /// of it's size on every side that grows, so growing one cell at a time
+    /// doesn't copy the grid every time.
+    pub fn grow_to(&mut self, coord: &Coord<K>) {
+        if self.grid.map.is_empty() {
+            self.grid = Grid { map: vec![self.default.clone()], width: 1, height: 1 };
+            self.origin = *coord;
+            return;
+        }
+        let (min, max) = self.bounds().unwrap();
+        let amount = |from: K, to: K, size: usize| {
+            if to > from { (to - from).as_usize().max(size / 2) } else { 0 }
+        };
+        let left = amount(coord.x, min.x, self.grid.width);
+        let right = amount(max.x, coord.x, self.grid.width);
+        let top = amount(coord.y, min.y, self.grid.height);
+        let bottom = amount(max.y, coord.y, self.grid.height);
+        if left + right + top + bottom == 0 {
+            return;
+        }
+        self.grid.pad(top, right, bottom, left, self.default.clone());
+        self.origin = Coord::new(
+            self.origin.x - K::try_from(left).unwrap_or_else(|_| panic!("Grid grew past the key type")),
+            self.origin.y - K::try_from(top).unwrap_or_else(|_| panic!("Grid grew past the key type")),
+        );
+    }
+
+    /// Mutable cell, growing the grid to it first.
+    pub fn get_mut_or_grow(&mut self, coord: &Coord<K>) -> &mut V {
+        self.grow_to(coord);
+        let local = self.to_local(coord).unwrap();
+        self.grid.get_val_mut(&local)
+    }
+
+    pub fn insert(&mut self, coord: &Coord<K>, value: V) {
+        *self.get_mut_or_grow(coord) = value;
+    }
+
+    /// Shrinks the grid to the cells that are not the default.
+    pub fn trim(&mut self)
+    where V: PartialEq
+    {
+        let default = self.default.clone();
+        match self.grid.crop_to(|v| *v != default) {
+            Some(min) => self.origin = self.to_global(&min),
+            None => self.origin = Coord::new(K::ZERO, K::ZERO),
+        }
+    }
+}
+
+impl<K, V> fmt::Display for OffsetGrid<K, V>
+where V: fmt::Display
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.grid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::direction::Direction;
+
+    #[test]
+    fn test_offset_lookups() {
+        let grid: Grid<char> = Grid::parse("ab\ncd").unwrap();
+        let offset = OffsetGrid::from_grid(grid, Coord::new(-5isize, 3), '.');
+        assert_eq!(offset.bounds(), Some((Coord::new(-5, 3), Coord::new(-4, 4))));
+        assert_eq!(offset.get(&Coord::new(-4, 4)), Some(&'d'));
+        assert_eq!(offset.get(&Coord::new(-6, 4)), None);
+        assert_eq!(*offset.get_or_default(&Coord::new(0, 0)), '.');
+        assert!(!offset.contains(&Coord::new(-3, 3)));
+
+        let c = Coord::new(-5, 3);
+        let east = c + Coord::<isize>::from(&Direction::E);
+        assert_eq!(offset.get(&east), Some(&'b'));
+        let neighbours: Vec<_> = offset.neighbours(&c, Neighbourhood::Touching).collect();
+        assert_eq!(neighbours, vec![Coord::new(-4, 3), Coord::new(-5, 4)]);
+        assert_eq!(offset.neighbours(&Coord::new(10, 10), Neighbourhood::Adjacent).count(), 0);
+
+        let points: Vec<_> = offset.iter_points().map(|p| (p.coord, *p.value)).collect();
+        assert_eq!(points[3], (Coord::new(-4, 4), 'd'));
+    }
+
+    #[test]
+    fn test_growing() {
+        let mut grid: OffsetGrid<i32, char> = OffsetGrid::new('.');
+        assert_eq!(grid.bounds(), None);
+        grid.insert(&Coord::new(2, -1), '#');
+        assert_eq!(grid.bounds(), Some((Coord::new(2, -1), Coord::new(2, -1))));
+
+        // Walks a square around the origin, growing in every direction
+        let mut position = Coord::new(2, -1);
+        for (direction, steps) in [(Direction::W, 4), (Direction::S, 3), (Direction::E, 6), (Direction::N, 5)] {
+            for _ in 0..steps {
+                position += OffsetGrid::<i32, char>::unit_step(&direction);
+                grid.insert(&position, '#');
+            }
+        }
+        assert_eq!(grid.get(&Coord::new(-2, 2)), Some(&'#'));
+        assert_eq!(grid.get(&Coord::new(4, -3)), Some(&'#'));
+        assert_eq!(*grid.get_or_default(&Coord::new(0, 0)), '.');
+        let (min, max) = grid.bounds().unwrap();
+        assert!(min.x <= -2 && min.y <= -3 && max.x >= 4 && max.y >= 2);
+
+        grid.trim();
+        assert_eq!(grid.bounds(), Some((Coord::new(-2, -3), Coord::new(4, 2))));
+        assert_eq!(grid.to_string(), "\
+......#
+......#
+#####.#
+#.....#
+#.....#
+#######
+");
+        assert_eq!(grid.iter_points().filter(|p| *p.value == '#').count(), 1 + 4 + 3 + 6 + 5);
+    }
+}
@@ -0,0 +1,157 @@
+use std::fmt;
+use std::hash::Hash;
+
+use super::coord::Coord;
+use super::direction::Direction;
+
+/// Which way `y` grows when moving north. Puzzle inputs are read top to
+/// bottom, so `Grid` is `Down` (north is `y - 1`), while plain coordinates
+/// follow the mathematical convention and are `Up` (north is `y + 1`).
+/// Value of [`Orientation::Y_AXIS`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum YAxis {
+    Up,
+    Down,
+}
+
+impl YAxis {
+    pub fn flipped(&self) -> Self {
+        match self {
+            YAxis::Up => YAxis::Down,
+            YAxis::Down => YAxis::Up,
+        }
+    }
+}
+
+/// Coordinate space with a fixed orientation, [`YUp`] or [`YDown`]. APIs
+/// that move by a [`Direction`] take the space as a type, so mixing up the
+/// two orientations doesn't compile.
+pub trait Orientation: Copy + Default + fmt::Debug + Eq + Hash + 'static {
+    const Y_AXIS: YAxis;
+
+    /// Same point in the other space, mirrored over `y = 0`.
+    fn convert<To: Orientation, T: num::Signed>(coord: Coord<T>) -> Coord<T> {
+        if Self::Y_AXIS == To::Y_AXIS {
+            coord
+        } else {
+            Coord::new(coord.x, -coord.y)
+        }
+    }
+}
+
+/// Mathematical space, north is `y + 1`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct YUp;
+
+/// Screen space of the puzzle inputs, north is `y - 1`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct YDown;
+
+impl Orientation for YUp {
+    const Y_AXIS: YAxis = YAxis::Up;
+}
+
+impl Orientation for YDown {
+    const Y_AXIS: YAxis = YAxis::Down;
+}
+
+/// Grids whose coordinates live in one space.
+pub trait Oriented {
+    type Orientation: Orientation;
+
+    /// Unit step of the direction in the grid's space.
+    fn unit_step<T: num::Signed>(direction: &Direction) -> Coord<T> {
+        direction.offset::<Self::Orientation, T>()
+    }
+}
+
+impl Direction {
+    /// Unit step of the direction in the space.
+    pub fn offset<O: Orientation, T: num::Signed>(&self) -> Coord<T> {
+        let north = match O::Y_AXIS {
+            YAxis::Up => T::one(),
+            YAxis::Down => -T::one(),
+        };
+        let (x, y) = match self {
+            Direction::N  => (T::zero(), north),
+            Direction::E  => (T::one(),  T::zero()),
+            Direction::S  => (T::zero(), -north),
+            Direction::W  => (-T::one(), T::zero()),
+            Direction::NE => (T::one(),  north),
+            Direction::NW => (-T::one(), north),
+            Direction::SE => (T::one(),  -north),
+            Direction::SW => (-T::one(), -north),
+        };
+        Coord::new(x, y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::direction::DIRECTIONS;
+    use super::super::grid::Grid;
+    use super::super::endless_grid::Grid as EndlessGrid;
+
+    #[test]
+    fn test_coord_is_y_up() {
+        let c: Coord<isize> = Coord::new(0, 0);
+        assert_eq!(c.get_neighbour(&Direction::N), Coord::new(0, 1));
+        assert_eq!(c.step::<YDown>(&Direction::N), Coord::new(0, -1));
+        for direction in DIRECTIONS {
+            assert_eq!(c.get_neighbour(&direction), c.step::<YUp>(&direction));
+            assert_eq!(Coord::<isize>::from(&direction), direction.offset::<YUp, isize>());
+        }
+    }
+
+    #[test]
+    fn test_grid_is_y_down() {
+        let grid = Grid::new(vec![vec![0; 3]; 3]);
+        let center = Coord::new(1, 1);
+        assert_eq!(grid.get_neighbour(&center, &Direction::N), Some(Coord::new(1, 0)));
+        for direction in DIRECTIONS {
+            let offset: Coord<isize> = Grid::<u8>::unit_step(&direction);
+            assert_eq!(offset, direction.offset::<YDown, isize>());
+            let expected = Coord::new((1 + offset.x) as usize, (1 + offset.y) as usize);
+            assert_eq!(grid.get_neighbour(&center, &direction), Some(expected));
+            assert_eq!(grid.get_neighbour_wrapping(&center, &direction), expected);
+            assert_eq!(grid.direction_iter(direction, center).next(), Some(expected));
+            assert_eq!(center.step::<YDown>(&direction), expected);
+        }
+    }
+
+    #[test]
+    fn test_conversions() {
+        let c: Coord<isize> = Coord::new(2, 5);
+        assert_eq!(YUp::convert::<YDown, _>(c), Coord::new(2, -5));
+        assert_eq!(YDown::convert::<YUp, _>(YUp::convert::<YDown, _>(c)), c);
+        assert_eq!(YUp::convert::<YUp, _>(c), c);
+        for direction in DIRECTIONS {
+            let up: Coord<isize> = direction.offset::<YUp, _>();
+            assert_eq!(YUp::convert::<YDown, _>(up), direction.offset::<YDown, _>());
+            // Stepping in one space is stepping in the other after converting
+            assert_eq!(YUp::convert::<YDown, _>(c.step::<YUp>(&direction)), YUp::convert::<YDown, _>(c).step::<YDown>(&direction));
+        }
+
+        let grid = Grid::new(vec![vec![0; 4]; 3]);
+        let bottom_left = Coord::new(0, 2);
+        assert_eq!(grid.to_y_up(&bottom_left), Coord::new(0, 0));
+        assert_eq!(grid.from_y_up(&grid.to_y_up(&Coord::new(3, 0))), Coord::new(3, 0));
+        // North in the grid is north after the conversion as well
+        let north = grid.get_neighbour(&bottom_left, &Direction::N).unwrap();
+        assert_eq!(grid.to_y_up(&north), Coord::new(0, 0).get_neighbour(&Direction::N));
+    }
+
+    #[test]
+    fn test_endless_grid_rows() {
+        let mut down: EndlessGrid<i32, char> = EndlessGrid::new(' ');
+        down.insert(Coord::new(0, 0), '×');
+        down.insert(Coord::new(1, 1), '×');
+        assert_eq!(down.to_string(), "× \n ×\n");
+        let up: EndlessGrid<i32, char, YUp> = down.with_orientation();
+        assert_eq!(up.to_string(), " ×\n× \n");
+        // North is the row above in both spaces
+        assert_eq!(EndlessGrid::<i32, char>::unit_step::<i32>(&Direction::N), Coord::new(0, -1));
+        assert_eq!(EndlessGrid::<i32, char, YUp>::unit_step::<i32>(&Direction::N), Coord::new(0, 1));
+    }
+}
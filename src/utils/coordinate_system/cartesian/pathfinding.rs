@@ -0,0 +1,326 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashSet, VecDeque};
+
+use super::coord::Coord;
+use super::grid::Grid;
+use super::neighbourhood::Neighbourhood;
+use super::point::Point;
+
+type StepFn<'a, V, T> = Box<dyn Fn(&Point<usize, &V>, &Point<usize, &V>) -> T + 'a>;
+
+/// Shortest path searches over the cells of a grid. Steps go from a cell to
+/// it's neighbours in the neighbourhood, if `passable(from, to)` allows it,
+/// and cost `cost(from, to)`. By default every step is passable and costs 1.
+pub struct PathFinder<'a, V> {
+    grid: &'a Grid<V>,
+    neighbourhood: Neighbourhood,
+    passable: StepFn<'a, V, bool>,
+    cost: StepFn<'a, V, usize>,
+}
+
+impl<'a, V> PathFinder<'a, V> {
+    pub fn new(grid: &'a Grid<V>, neighbourhood: Neighbourhood) -> Self {
+        PathFinder {
+            grid,
+            neighbourhood,
+            passable: Box::new(|_, _| true),
+            cost: Box::new(|_, _| 1),
+        }
+    }
+
+    pub fn passable(mut self, passable: impl Fn(&Point<usize, &V>, &Point<usize, &V>) -> bool + 'a) -> Self {
+        self.passable = Box::new(passable);
+        self
+    }
+
+    pub fn cost(mut self, cost: impl Fn(&Point<usize, &V>, &Point<usize, &V>) -> usize + 'a) -> Self {
+        self.cost = Box::new(cost);
+        self
+    }
+
+    /// Passable neighbours of the cell with the cost of the step.
+    fn steps(&self, from: Coord<usize>) -> impl Iterator<Item = (Coord<usize>, usize)> + '_ {
+        let from = self.grid.get_point(&from);
+        self.grid.neighbours(from.coord, self.neighbourhood).filter_map(move |to| {
+            let to = self.grid.get_point(&to);
+            if (self.passable)(&from, &to) {
+                Some((to.coord, (self.cost)(&from, &to)))
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Breadth first search from all the starts, ignoring the step costs.
+    /// Stops as soon as a cell matching `is_target` is reached.
+    pub fn bfs(&self, starts: &[Coord<usize>], is_target: impl Fn(&Coord<usize>) -> bool) -> DistanceMap {
+        let mut map = DistanceMap::new(self.grid.width, self.grid.height);
+        let mut queue: VecDeque<Coord<usize>> = VecDeque::new();
+        for start in starts {
+            map.set_start(start);
+            queue.push_back(*start);
+        }
+
+        while let Some(current) = queue.pop_front() {
+            if is_target(&current) {
+                map.reached = Some(current);
+                break;
+            }
+            let distance = map.distance(&current).unwrap() + 1;
+            for (next, _) in self.steps(current) {
+                if map.relax(&current, &next, distance) {
+                    queue.push_back(next);
+                }
+            }
+        }
+        map
+    }
+
+    /// Dijkstra from all the starts. Stops as soon as a cell matching
+    /// `is_target` is reached.
+    pub fn dijkstra(&self, starts: &[Coord<usize>], is_target: impl Fn(&Coord<usize>) -> bool) -> DistanceMap {
+        self.best_first(starts, is_target, |_| 0)
+    }
+
+    /// A* towards the target, the heuristic must never overestimate the
+    /// remaining cost (e.g. manhattan distance for unit costs).
+    pub fn astar(
+        &self,
+        starts: &[Coord<usize>],
+        target: Coord<usize>,
+        heuristic: impl Fn(&Coord<usize>) -> usize
+    ) -> Option<(usize, Vec<Coord<usize>>)> {
+        let map = self.best_first(starts, |c| *c == target, heuristic);
+        let path = map.path_to(&target)?;
+        Some((map.distance(&target)?, path))
+    }
+
+    fn best_first(
+        &self,
+        starts: &[Coord<usize>],
+        is_target: impl Fn(&Coord<usize>) -> bool,
+        heuristic: impl Fn(&Coord<usize>) -> usize
+    ) -> DistanceMap {
+        best_first(self.grid.width, self.grid.height, starts, |c| self.steps(c), is_target, heuristic)
+    }
+}
+
+/// Best first search over the cells of a `width x height` grid, `steps`
+/// gives the cells reachable from a cell with the cost of the step. Steps
+/// don't have to go to neighbours, [`MazeGraph`] steps along whole
+/// corridors.
+///
+/// [`MazeGraph`]: super::MazeGraph
+pub(super) fn best_first<I>(
+    width: usize,
+    height: usize,
+    starts: &[Coord<usize>],
+    steps: impl Fn(Coord<usize>) -> I,
+    is_target: impl Fn(&Coord<usize>) -> bool,
+    heuristic: impl Fn(&Coord<usize>) -> usize
+) -> DistanceMap
+where I: Iterator<Item = (Coord<usize>, usize)>
+{
+    let mut map = DistanceMap::new(width, height);
+    let mut heap: BinaryHeap<Reverse<(usize, usize, Coord<usize>)>> = BinaryHeap::new();
+    for start in starts {
+        map.set_start(start);
+        heap.push(Reverse((heuristic(start), 0, *start)));
+    }
+
+    while let Some(Reverse((_, distance, current))) = heap.pop() {
+        if map.distance(&current) != Some(distance) {
+            // Already reached with a shorter distance
+            continue;
+        }
+        if is_target(&current) {
+            map.reached = Some(current);
+            break;
+        }
+        for (next, cost) in steps(current) {
+            let next_distance = distance + cost;
+            if map.relax(&current, &next, next_distance) {
+                heap.push(Reverse((next_distance + heuristic(&next), next_distance, next)));
+            }
+        }
+    }
+    map
+}
+
+/// Result of a search: distance of every reached cell and the cells it
+/// was reached from on the shortest paths.
+pub struct DistanceMap {
+    pub distances: Grid<Option<usize>>,
+    predecessors: Grid<Vec<Coord<usize>>>,
+    /// Target cell the search stopped on
+    pub reached: Option<Coord<usize>>,
+}
+
+impl DistanceMap {
+    fn new(width: usize, height: usize) -> Self {
+        DistanceMap {
+            distances: Grid { map: vec![None; width * height], width, height },
+            predecessors: Grid { map: vec![vec![]; width * height], width, height },
+            reached: None,
+        }
+    }
+
+    fn set_start(&mut self, start: &Coord<usize>) {
+        *self.distances.get_val_mut(start) = Some(0);
+    }
+
+    /// Records the step if it's on a shortest path. Returns true when it
+    /// found a new shorter distance to `to`. Ties over a step of cost 0
+    /// aren't recorded, they would make the predecessors loop.
+    fn relax(&mut self, from: &Coord<usize>, to: &Coord<usize>, distance: usize) -> bool {
+        match *self.distances.get_val(to) {
+            Some(current) if current < distance => false,
+            Some(current) if current == distance => {
+                if self.distance(from) < Some(distance) {
+                    self.predecessors.get_val_mut(to).push(*from);
+                }
+                false
+            },
+            _ => {
+                *self.distances.get_val_mut(to) = Some(distance);
+                *self.predecessors.get_val_mut(to) = vec![*from];
+                true
+            }
+        }
+    }
+
+    pub fn distance(&self, coord: &Coord<usize>) -> Option<usize> {
+        *self.distances.get_val(coord)
+    }
+
+    /// One of the shortest paths from a start to the target, both included.
+    pub fn path_to(&self, target: &Coord<usize>) -> Option<Vec<Coord<usize>>> {
+        self.distance(target)?;
+        let mut path = vec![*target];
+        let mut current = *target;
+        while let Some(previous) = self.predecessors.get_val(&current).first() {
+            path.push(*previous);
+            current = *previous;
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    /// Every shortest path from a start to the target.
+    pub fn all_paths_to(&self, target: &Coord<usize>) -> Vec<Vec<Coord<usize>>> {
+        if self.distance(target).is_none() {
+            return vec![];
+        }
+        let predecessors = self.predecessors.get_val(target);
+        if predecessors.is_empty() {
+            return vec![vec![*target]];
+        }
+        predecessors.iter().flat_map(|previous| {
+            self.all_paths_to(previous).into_iter().map(|mut path| {
+                path.push(*target);
+                path
+            })
+        }).collect()
+    }
+
+    /// Cells that are on any of the shortest paths to the target.
+    pub fn cells_on_paths_to(&self, target: &Coord<usize>) -> HashSet<Coord<usize>> {
+        let mut cells = HashSet::new();
+        if self.distance(target).is_none() {
+            return cells;
+        }
+        let mut stack = vec![*target];
+        while let Some(current) = stack.pop() {
+            if cells.insert(current) {
+                stack.extend(self.predecessors.get_val(&current));
+            }
+        }
+        cells
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn maze() -> Grid<char> {
+        Grid::parse("\
+S..#....
+.#.#.##.
+.#...#..
+.####.#.
+......#E").unwrap()
+    }
+
+    fn open(_: &Point<usize, &char>, to: &Point<usize, &char>) -> bool {
+        *to.value != '#'
+    }
+
+    #[test]
+    fn test_bfs() {
+        let grid = maze();
+        let finder = PathFinder::new(&grid, Neighbourhood::Touching).passable(open);
+        let map = finder.bfs(&[Coord::new(0, 0)], |_| false);
+        assert_eq!(map.distance(&Coord::new(7, 4)), Some(15));
+        assert_eq!(map.distance(&Coord::new(3, 0)), None);
+
+        let path = map.path_to(&Coord::new(7, 4)).unwrap();
+        assert_eq!(path.len(), 16);
+        assert_eq!(path[0], Coord::new(0, 0));
+        assert!(path.windows(2).all(|w| w[0].x.abs_diff(w[1].x) + w[0].y.abs_diff(w[1].y) == 1));
+    }
+
+    #[test]
+    fn test_multi_source_and_early_exit() {
+        let grid = maze();
+        let finder = PathFinder::new(&grid, Neighbourhood::Touching).passable(open);
+        let map = finder.bfs(&[Coord::new(0, 0), Coord::new(7, 4)], |c| *c == Coord::new(4, 2));
+        assert_eq!(map.reached, Some(Coord::new(4, 2)));
+        assert_eq!(map.distance(&Coord::new(4, 2)), Some(6));
+        // Cells farther than the target are never reached
+        assert_eq!(map.distance(&Coord::new(5, 4)), None);
+    }
+
+    #[test]
+    fn test_all_paths() {
+        let grid = Grid::new(vec![vec!['.'; 3]; 3]);
+        let finder = PathFinder::new(&grid, Neighbourhood::Touching);
+        let map = finder.bfs(&[Coord::new(0, 0)], |_| false);
+        assert_eq!(map.all_paths_to(&Coord::new(2, 2)).len(), 6);
+        assert_eq!(map.cells_on_paths_to(&Coord::new(2, 1)).len(), 6);
+    }
+
+    #[test]
+    fn test_weighted() {
+        let grid: Grid<u8> = Grid::parse("\
+1111
+9991
+1111").unwrap();
+        let finder = PathFinder::new(&grid, Neighbourhood::Touching).cost(|_, to| *to.value as usize);
+        let target = Coord::new(0, 2);
+        let map = finder.dijkstra(&[Coord::new(0, 0)], |c| *c == target);
+        assert_eq!(map.distance(&target), Some(8));
+
+        let (distance, path) = finder.astar(&[Coord::new(0, 0)], target, |c| c.x + 2 - c.y).unwrap();
+        assert_eq!(distance, 8);
+        assert_eq!(path.len(), 9);
+
+        let wrapping = PathFinder::new(&grid, Neighbourhood::TouchingWrapping).cost(|_, to| *to.value as usize);
+        let map = wrapping.dijkstra(&[Coord::new(0, 0)], |_| false);
+        assert_eq!(map.distance(&target), Some(1));
+    }
+
+    #[test]
+    fn test_zero_cost() {
+        let grid = Grid::new(vec![vec!['.'; 3]; 2]);
+        let finder = PathFinder::new(&grid, Neighbourhood::Touching).cost(|_, _| 0);
+        let map = finder.dijkstra(&[Coord::new(0, 0), Coord::new(1, 0)], |_| false);
+        let target = Coord::new(2, 1);
+        assert_eq!(map.distance(&target), Some(0));
+        assert_eq!(map.path_to(&Coord::new(1, 0)), Some(vec![Coord::new(1, 0)]));
+        let path = map.path_to(&target).unwrap();
+        assert!(path.len() <= 4);
+        assert!(map.all_paths_to(&target).iter().all(|p| p.last() == Some(&target)));
+        assert!(map.cells_on_paths_to(&target).len() <= 6);
+    }
+}
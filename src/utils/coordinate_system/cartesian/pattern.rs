@@ -0,0 +1,178 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use super::coord::Coord;
+use super::direction::{Direction, DIRECTIONS};
+use super::grid::Grid;
+use super::transform::{Symmetry, SYMMETRIES};
+
+const BASE: u64 = 0x100000001b3;
+
+fn cell_hash<V: Hash>(value: &V) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+impl<V> Grid<V>
+where V: PartialEq + Hash
+{
+    /// Top left corners of every place the pattern matches, in row order.
+    /// `None` cells of the pattern match anything. When the pattern has a
+    /// row without wildcards, only the places where a rolling hash finds
+    /// that row are checked cell by cell.
+    pub fn find_pattern(&self, pattern: &Grid<Option<V>>) -> Vec<Coord<usize>> {
+        if pattern.width == 0 || pattern.height == 0 || pattern.width > self.width || pattern.height > self.height {
+            return vec![];
+        }
+        let anchor = (0..pattern.height).find(|y| pattern.row(*y).iter().all(|v| v.is_some()));
+        let Some(anchor) = anchor else {
+            let (max_x, max_y) = (self.width - pattern.width, self.height - pattern.height);
+            return (0..=max_y)
+                .flat_map(|y| (0..=max_x).map(move |x| Coord::new(x, y)))
+                .filter(|c| self.matches_at(pattern, c))
+                .collect();
+        };
+
+        let hashes: Vec<u64> = self.map.iter().map(cell_hash).collect();
+        let width = pattern.width;
+        let target = pattern.row(anchor).iter()
+            .fold(0u64, |h, v| h.wrapping_mul(BASE).wrapping_add(cell_hash(v.as_ref().unwrap())));
+        // Weight of the cell leaving the window
+        let leaving = (1..width).fold(1u64, |p, _| p.wrapping_mul(BASE));
+
+        let mut matches = vec![];
+        for y in 0..=self.height - pattern.height {
+            let row = &hashes[(y + anchor) * self.width..(y + anchor + 1) * self.width];
+            let mut hash = row[..width].iter().fold(0u64, |h, v| h.wrapping_mul(BASE).wrapping_add(*v));
+            for x in 0..=self.width - width {
+                if x > 0 {
+                    hash = hash.wrapping_sub(row[x - 1].wrapping_mul(leaving))
+                        .wrapping_mul(BASE)
+                        .wrapping_add(row[x + width - 1]);
+                }
+                let corner = Coord::new(x, y);
+                if hash == target && self.matches_at(pattern, &corner) {
+                    matches.push(corner);
+                }
+            }
+        }
+        matches
+    }
+
+    /// Compares every cell of the pattern placed with it's top left corner
+    /// at `corner`.
+    pub fn matches_at(&self, pattern: &Grid<Option<V>>, corner: &Coord<usize>) -> bool {
+        if corner.x + pattern.width > self.width || corner.y + pattern.height > self.height {
+            return false;
+        }
+        pattern.iter_points().all(|p| match p.value {
+            Some(value) => self.get_val(&Coord::new(corner.x + p.coord.x, corner.y + p.coord.y)) == value,
+            None => true,
+        })
+    }
+
+    /// Starts and directions of every place the word is read along one of
+    /// the eight directions. Palindromes are found in both directions.
+    pub fn find_word(&self, word: &[V]) -> Vec<(Coord<usize>, Direction)> {
+        let Some((first, rest)) = word.split_first() else {
+            return vec![];
+        };
+        let mut found = vec![];
+        for start in self.iter_points().filter(|p| p.value == first) {
+            for direction in DIRECTIONS {
+                let mut ray = self.ray(direction, start.coord);
+                if rest.iter().all(|v| ray.next().is_some_and(|p| p.value == v)) {
+                    found.push((start.coord, direction));
+                }
+            }
+        }
+        found
+    }
+}
+
+impl<V> Grid<V>
+where V: PartialEq + Hash + Clone
+{
+    /// Matches of every rotation and reflection of the pattern, each with
+    /// the symmetry applied to the pattern. Symmetries that give the same
+    /// pattern as an earlier one are skipped, so a place is never reported
+    /// twice for the same image.
+    pub fn find_pattern_symmetric(&self, pattern: &Grid<Option<V>>) -> Vec<(Coord<usize>, Symmetry)> {
+        let mut images: Vec<Grid<Option<V>>> = vec![];
+        let mut found = vec![];
+        for symmetry in SYMMETRIES {
+            let image = pattern.transformed(symmetry);
+            if images.contains(&image) {
+                continue;
+            }
+            found.extend(self.find_pattern(&image).into_iter().map(|c| (c, symmetry)));
+            images.push(image);
+        }
+        found
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pattern(s: &str) -> Grid<Option<char>> {
+        let grid: Grid<char> = Grid::parse(s).unwrap();
+        Grid { map: grid.map.iter().map(|c| (*c != '?').then_some(*c)).collect(), width: grid.width, height: grid.height }
+    }
+
+    fn naive(grid: &Grid<char>, pattern: &Grid<Option<char>>) -> Vec<Coord<usize>> {
+        grid.iter_coords().filter(|c| grid.matches_at(pattern, c)).collect()
+    }
+
+    #[test]
+    fn test_find_pattern() {
+        let grid: Grid<char> = Grid::parse("\
+abab.
+babab
+abab.
+.a.ba").unwrap();
+        let exact = pattern("ab\nba");
+        assert_eq!(grid.find_pattern(&exact), vec![Coord::new(0, 0), Coord::new(2, 0), Coord::new(1, 1)]);
+        for p in ["a?\n?a", "??\n??", "b\n?\nb", "?b?\nbab", "abab.\nbabab\nabab.\n.a.ba", "abcdef"] {
+            let p = pattern(p);
+            assert_eq!(grid.find_pattern(&p), naive(&grid, &p), "{:?}", p);
+        }
+    }
+
+    #[test]
+    fn test_symmetries() {
+        let grid: Grid<char> = Grid::parse("\
+#....
+##..#
+...##
+.....").unwrap();
+        // L shape with a wildcard corner, it's symmetric along a diagonal
+        // so only 4 of the 8 images are searched
+        let shape = pattern("#?\n##");
+        let found = grid.find_pattern_symmetric(&shape);
+        let corners: Vec<_> = found.iter().map(|(c, _)| *c).collect();
+        assert_eq!(corners, vec![Coord::new(0, 0), Coord::new(3, 1)]);
+        let square = pattern("##\n##");
+        assert_eq!(grid.find_pattern_symmetric(&square), vec![]);
+    }
+
+    #[test]
+    fn test_find_word() {
+        let grid: Grid<char> = Grid::parse("\
+XMAS
+MM.A
+A.AM
+S..X").unwrap();
+        let word: Vec<char> = "XMAS".chars().collect();
+        let mut found = grid.find_word(&word);
+        found.sort_by_key(|(c, _)| (c.y, c.x));
+        assert_eq!(found, vec![
+            (Coord::new(0, 0), Direction::E),
+            (Coord::new(0, 0), Direction::S),
+            (Coord::new(3, 3), Direction::N),
+        ]);
+        assert!(grid.find_word(&[]).is_empty());
+    }
+}
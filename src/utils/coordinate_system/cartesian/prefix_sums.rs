@@ -0,0 +1,282 @@
+use super::coord::Coord;
+use super::grid::Grid;
+
+/// Corners of the rectangle between two cells, as `(min, max)`.
+fn corners(a: &Coord<usize>, b: &Coord<usize>) -> (Coord<usize>, Coord<usize>) {
+    (Coord::new(a.x.min(b.x), a.y.min(b.y)), Coord::new(a.x.max(b.x), a.y.max(b.y)))
+}
+
+/// Summed-area table, the sum of any rectangle of values in O(1).
+#[derive(Debug, Clone)]
+pub struct SummedArea<T> {
+    /// Sum of the cells above and left of every corner, `(width + 1) x (height + 1)`
+    sums: Vec<T>,
+    width: usize,
+    height: usize,
+}
+
+impl<T> SummedArea<T>
+where T: num::Num + Copy
+{
+    pub fn new<V>(grid: &Grid<V>, value: impl Fn(&V) -> T) -> Self {
+        Self::from_values(grid.width, grid.height, |c| value(grid.get_val(c)))
+    }
+
+    fn from_values(width: usize, height: usize, value: impl Fn(&Coord<usize>) -> T) -> Self {
+        let stride = width + 1;
+        let mut sums = vec![T::zero(); stride * (height + 1)];
+        for y in 0..height {
+            let mut row = T::zero();
+            for x in 0..width {
+                row = row + value(&Coord::new(x, y));
+                sums[(y + 1) * stride + x + 1] = sums[y * stride + x + 1] + row;
+            }
+        }
+        SummedArea { sums, width, height }
+    }
+
+    fn corner(&self, x: usize, y: usize) -> T {
+        self.sums[y * (self.width + 1) + x]
+    }
+
+    /// Sum of the rectangle between the cells, both inclusive and in any
+    /// order.
+    pub fn sum(&self, a: &Coord<usize>, b: &Coord<usize>) -> T {
+        let (min, max) = corners(a, b);
+        assert!(max.x < self.width && max.y < self.height, "Rectangle {} - {} is out of the table", min, max);
+        // Added first so unsigned sums never go below zero
+        self.corner(max.x + 1, max.y + 1) + self.corner(min.x, min.y)
+            - self.corner(min.x, max.y + 1)
+            - self.corner(max.x + 1, min.y)
+    }
+
+    pub fn total(&self) -> T {
+        self.corner(self.width, self.height)
+    }
+}
+
+impl<V> Grid<V> {
+    pub fn summed_area<T: num::Num + Copy>(&self, value: impl Fn(&V) -> T) -> SummedArea<T> {
+        SummedArea::new(self, value)
+    }
+
+    /// Table counting the cells matching the predicate.
+    pub fn count_table(&self, predicate: impl Fn(&V) -> bool) -> SummedArea<usize> {
+        SummedArea::new(self, |v| predicate(v) as usize)
+    }
+}
+
+/// Batched rectangle updates, every update is O(1) and [`build`] adds them
+/// all up at once.
+///
+/// [`build`]: DifferenceArray::build
+#[derive(Debug, Clone)]
+pub struct DifferenceArray<T> {
+    diff: Vec<T>,
+    width: usize,
+    height: usize,
+}
+
+impl<T> DifferenceArray<T>
+where T: num::Signed + Copy
+{
+    pub fn new(width: usize, height: usize) -> Self {
+        DifferenceArray { diff: vec![T::zero(); (width + 1) * (height + 1)], width, height }
+    }
+
+    /// Adds the value to the rectangle between the cells, both inclusive.
+    pub fn add(&mut self, a: &Coord<usize>, b: &Coord<usize>, value: T) {
+        let (min, max) = corners(a, b);
+        assert!(max.x < self.width && max.y < self.height, "Rectangle {} - {} is out of the array", min, max);
+        let stride = self.width + 1;
+        self.diff[min.y * stride + min.x] = self.diff[min.y * stride + min.x] + value;
+        self.diff[min.y * stride + max.x + 1] = self.diff[min.y * stride + max.x + 1] - value;
+        self.diff[(max.y + 1) * stride + min.x] = self.diff[(max.y + 1) * stride + min.x] - value;
+        self.diff[(max.y + 1) * stride + max.x + 1] = self.diff[(max.y + 1) * stride + max.x + 1] + value;
+    }
+
+    /// Total of the updates of every cell.
+    pub fn build(&self) -> Grid<T> {
+        let stride = self.width + 1;
+        let mut map = vec![T::zero(); self.width * self.height];
+        for y in 0..self.height {
+            let mut row = T::zero();
+            for x in 0..self.width {
+                row = row + self.diff[y * stride + x];
+                let above = if y > 0 { map[(y - 1) * self.width + x] } else { T::zero() };
+                map[y * self.width + x] = above + row;
+            }
+        }
+        Grid { map, width: self.width, height: self.height }
+    }
+}
+
+/// Coordinate compression of one axis. Every compressed value gets a cell
+/// of it's own and the gaps between them become a single cell each.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompressedAxis {
+    /// First value of every cell, followed by the end of the last one
+    starts: Vec<usize>,
+}
+
+impl CompressedAxis {
+    pub fn new(values: impl IntoIterator<Item = usize>) -> Self {
+        let mut starts: Vec<usize> = values.into_iter().flat_map(|v| [v, v + 1]).collect();
+        starts.sort_unstable();
+        starts.dedup();
+        CompressedAxis { starts }
+    }
+
+    /// Number of cells.
+    pub fn len(&self) -> usize {
+        self.starts.len().saturating_sub(1)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Cell containing the value.
+    pub fn index(&self, value: usize) -> Option<usize> {
+        let i = match self.starts.binary_search(&value) {
+            Ok(i) => i,
+            Err(i) => i.checked_sub(1)?,
+        };
+        (i < self.len()).then_some(i)
+    }
+
+    pub fn start(&self, index: usize) -> usize {
+        self.starts[index]
+    }
+
+    /// Number of values in the cell.
+    pub fn size(&self, index: usize) -> usize {
+        self.starts[index + 1] - self.starts[index]
+    }
+}
+
+/// Grid coordinates compressed on both axes, a grid of the cells stands in
+/// for a huge sparse one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Compressed {
+    pub xs: CompressedAxis,
+    pub ys: CompressedAxis,
+}
+
+impl Compressed {
+    pub fn new<'c>(coords: impl IntoIterator<Item = &'c Coord<usize>> + Clone) -> Self {
+        Compressed {
+            xs: CompressedAxis::new(coords.clone().into_iter().map(|c| c.x)),
+            ys: CompressedAxis::new(coords.into_iter().map(|c| c.y)),
+        }
+    }
+
+    /// Grid with a cell for every compressed cell.
+    pub fn grid<V: Clone>(&self, value: V) -> Grid<V> {
+        let (width, height) = (self.xs.len(), self.ys.len());
+        Grid { map: vec![value; width * height], width, height }
+    }
+
+    /// Compressed cell containing the coordinate.
+    pub fn cell(&self, coord: &Coord<usize>) -> Option<Coord<usize>> {
+        Some(Coord::new(self.xs.index(coord.x)?, self.ys.index(coord.y)?))
+    }
+
+    /// Compressed cells covering the rectangle between the coordinates. It's
+    /// exact when the corners were part of the compression.
+    pub fn rect(&self, a: &Coord<usize>, b: &Coord<usize>) -> Option<(Coord<usize>, Coord<usize>)> {
+        let (min, max) = corners(a, b);
+        Some((self.cell(&min)?, self.cell(&max)?))
+    }
+
+    /// Number of original cells in the compressed cell.
+    pub fn area(&self, cell: &Coord<usize>) -> usize {
+        self.xs.size(cell.x) * self.ys.size(cell.y)
+    }
+
+    /// Table of a compressed grid where every cell's value counts once for
+    /// every original cell it covers.
+    pub fn summed_area<V, T>(&self, grid: &Grid<V>, value: impl Fn(&V) -> T) -> SummedArea<T>
+    where T: num::Num + num::NumCast + Copy
+    {
+        SummedArea::from_values(grid.width, grid.height, |c| {
+            let area: T = num::NumCast::from(self.area(c)).expect("Cell area does not fit the sum type");
+            value(grid.get_val(c)) * area
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid() -> Grid<u32> {
+        Grid::new(vec![
+            vec![1, 2, 3, 4],
+            vec![5, 6, 7, 8],
+            vec![9, 10, 11, 12],
+        ])
+    }
+
+    #[test]
+    fn test_summed_area() {
+        let grid = grid();
+        let table = grid.summed_area(|v| *v);
+        assert_eq!(table.total(), 78);
+        for a in grid.iter_coords() {
+            for b in grid.iter_coords() {
+                let (min, max) = corners(&a, &b);
+                let expected: u32 = grid.iter_points()
+                    .filter(|p| (min.x..=max.x).contains(&p.coord.x) && (min.y..=max.y).contains(&p.coord.y))
+                    .map(|p| *p.value)
+                    .sum();
+                assert_eq!(table.sum(&a, &b), expected);
+            }
+        }
+        let odd = grid.count_table(|v| v % 2 == 1);
+        assert_eq!(odd.sum(&Coord::new(1, 0), &Coord::new(2, 2)), 3);
+    }
+
+    #[test]
+    fn test_difference_array() {
+        let mut diff: DifferenceArray<i64> = DifferenceArray::new(4, 3);
+        diff.add(&Coord::new(0, 0), &Coord::new(2, 1), 1);
+        diff.add(&Coord::new(3, 2), &Coord::new(1, 1), 10);
+        diff.add(&Coord::new(3, 2), &Coord::new(3, 2), -5);
+        assert_eq!(diff.build().map, vec![
+            1, 1, 1, 0,
+            1, 11, 11, 10,
+            0, 10, 10, 5,
+        ]);
+    }
+
+    #[test]
+    fn test_compressed() {
+        let axis = CompressedAxis::new([10, 3, 10]);
+        assert_eq!(axis.len(), 3);
+        assert_eq!((axis.index(2), axis.index(3), axis.index(4), axis.index(9), axis.index(10), axis.index(11)),
+            (None, Some(0), Some(1), Some(1), Some(2), None));
+        assert_eq!((axis.start(1), axis.size(1)), (4, 6));
+
+        // 1000 x 1000 square of ones with a hole in the middle
+        let corners = [Coord::new(0, 0), Coord::new(999, 999), Coord::new(400, 400), Coord::new(599, 599)];
+        let compressed = Compressed::new(&corners);
+        let mut grid = compressed.grid(1u64);
+        let (hole_min, hole_max) = compressed.rect(&corners[2], &corners[3]).unwrap();
+        for coord in grid.iter_coords().collect::<Vec<_>>() {
+            if (hole_min.x..=hole_max.x).contains(&coord.x) && (hole_min.y..=hole_max.y).contains(&coord.y) {
+                *grid.get_val_mut(&coord) = 0;
+            }
+        }
+        let table = compressed.summed_area(&grid, |v| *v);
+        assert_eq!(table.total(), 1_000_000 - 200 * 200);
+        let (min, max) = compressed.rect(&Coord::new(0, 0), &Coord::new(400, 999)).unwrap();
+        assert_eq!(table.sum(&min, &max), 401 * 1000 - 200);
+
+        let mut diff: DifferenceArray<i64> = DifferenceArray::new(grid.width, grid.height);
+        diff.add(&hole_min, &hole_max, 1);
+        let covered = diff.build();
+        let area: usize = covered.iter_points().filter(|p| *p.value > 0).map(|p| compressed.area(&p.coord)).sum();
+        assert_eq!(area, 200 * 200);
+    }
+}
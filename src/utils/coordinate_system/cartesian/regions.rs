@@ -0,0 +1,209 @@
+use std::collections::VecDeque;
+
+use super::coord::Coord;
+use super::direction::Direction;
+use super::grid::Grid;
+use super::neighbourhood::Neighbourhood;
+use super::point::Point;
+
+/// Corners of a cell as the two touching directions and the diagonal
+/// between them.
+const CORNERS: [(Direction, Direction, Direction); 4] = [
+    (Direction::N, Direction::E, Direction::NE),
+    (Direction::E, Direction::S, Direction::SE),
+    (Direction::S, Direction::W, Direction::SW),
+    (Direction::W, Direction::N, Direction::NW),
+];
+
+/// Connected cells with the same label.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Region {
+    pub id: usize,
+    pub area: usize,
+    /// Number of cell edges on the border of the region
+    pub perimeter: usize,
+    /// Number of straight sides of the border, the same as the number of
+    /// it's corners
+    pub sides: usize,
+    /// Top left corner of the bounding box
+    pub min: Coord<usize>,
+    /// Bottom right corner of the bounding box, inclusive
+    pub max: Coord<usize>,
+}
+
+/// Connected-component labelling of a grid.
+pub struct Regions {
+    /// Region id of every cell, an index into `regions`
+    pub labels: Grid<usize>,
+    pub regions: Vec<Region>,
+}
+
+impl Regions {
+    pub fn get(&self, coord: &Coord<usize>) -> &Region {
+        &self.regions[*self.labels.get_val(coord)]
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Region> {
+        self.regions.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.regions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.regions.is_empty()
+    }
+}
+
+impl<V> Grid<V> {
+    /// Cells reachable from the seed through neighbours for which
+    /// `same_region(from, to)` holds, in the order they were found.
+    pub fn flood_fill(
+        &self,
+        seed: Coord<usize>,
+        neighbourhood: Neighbourhood,
+        same_region: impl Fn(&Point<usize, &V>, &Point<usize, &V>) -> bool
+    ) -> Vec<Coord<usize>> {
+        let mut visited = vec![false; self.map.len()];
+        self.fill(seed, neighbourhood, &same_region, &mut visited)
+    }
+
+    /// Splits the grid into regions of connected cells, `same_region` is
+    /// called for neighbouring cells and should be symmetric. Perimeter and
+    /// sides are measured on the cell edges, edges of the grid included.
+    pub fn regions(
+        &self,
+        neighbourhood: Neighbourhood,
+        same_region: impl Fn(&Point<usize, &V>, &Point<usize, &V>) -> bool
+    ) -> Regions {
+        let mut visited = vec![false; self.map.len()];
+        let mut labels = Grid { map: vec![0; self.map.len()], width: self.width, height: self.height };
+        let mut cells = vec![];
+        for coord in self.iter_coords() {
+            if visited[self.get_index(&coord)] {
+                continue;
+            }
+            let region = self.fill(coord, neighbourhood, &same_region, &mut visited);
+            for c in &region {
+                *labels.get_val_mut(c) = cells.len();
+            }
+            cells.push(region);
+        }
+
+        let regions = cells.iter().enumerate().map(|(id, region)| {
+            let mut perimeter = 0;
+            let mut sides = 0;
+            let mut min = region[0];
+            let mut max = region[0];
+            for coord in region {
+                let same = |direction: &Direction| {
+                    labels.get_neighbour(coord, direction).is_some_and(|n| *labels.get_val(&n) == id)
+                };
+                for (a, b, diagonal) in &CORNERS {
+                    if !same(a) {
+                        perimeter += 1;
+                    }
+                    match (same(a), same(b)) {
+                        (false, false) => sides += 1,
+                        (true, true) if !same(diagonal) => sides += 1,
+                        _ => {}
+                    }
+                }
+                min = Coord::new(min.x.min(coord.x), min.y.min(coord.y));
+                max = Coord::new(max.x.max(coord.x), max.y.max(coord.y));
+            }
+            Region { id, area: region.len(), perimeter, sides, min, max }
+        }).collect();
+
+        Regions { labels, regions }
+    }
+
+    fn fill(
+        &self,
+        seed: Coord<usize>,
+        neighbourhood: Neighbourhood,
+        same_region: &impl Fn(&Point<usize, &V>, &Point<usize, &V>) -> bool,
+        visited: &mut [bool]
+    ) -> Vec<Coord<usize>> {
+        let mut region = vec![seed];
+        let mut queue = VecDeque::from([seed]);
+        visited[self.get_index(&seed)] = true;
+        while let Some(current) = queue.pop_front() {
+            let from = self.get_point(&current);
+            for next in self.neighbours(current, neighbourhood) {
+                let index = self.get_index(&next);
+                if visited[index] || !same_region(&from, &self.get_point(&next)) {
+                    continue;
+                }
+                visited[index] = true;
+                region.push(next);
+                queue.push_back(next);
+            }
+        }
+        region
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn same_value(a: &Point<usize, &char>, b: &Point<usize, &char>) -> bool {
+        a.value == b.value
+    }
+
+    #[test]
+    fn test_flood_fill() {
+        let grid: Grid<char> = Grid::parse("\
+..#
+.#.
+#..").unwrap();
+        let filled = grid.flood_fill(Coord::new(0, 0), Neighbourhood::Touching, same_value);
+        assert_eq!(filled.len(), 3);
+        let filled = grid.flood_fill(Coord::new(0, 0), Neighbourhood::Adjacent, same_value);
+        assert_eq!(filled.len(), 6);
+        let filled = grid.flood_fill(Coord::new(2, 0), Neighbourhood::AdjacentWrapping, same_value);
+        assert_eq!(filled.len(), 3);
+    }
+
+    #[test]
+    fn test_regions() {
+        let grid: Grid<char> = Grid::parse("\
+AAAA
+BBCD
+BBCC
+EEEC").unwrap();
+        let regions = grid.regions(Neighbourhood::Touching, same_value);
+        assert_eq!(regions.len(), 5);
+        let stats: Vec<_> = regions.iter().map(|r| (r.area, r.perimeter, r.sides)).collect();
+        assert_eq!(stats, vec![(4, 10, 4), (4, 8, 4), (4, 10, 8), (1, 4, 4), (3, 8, 4)]);
+
+        let c = regions.get(&Coord::new(2, 1));
+        assert_eq!((c.min, c.max), (Coord::new(2, 1), Coord::new(3, 3)));
+        assert_eq!(*regions.labels.get_val(&Coord::new(3, 3)), c.id);
+    }
+
+    #[test]
+    fn test_sides_with_holes() {
+        let grid: Grid<char> = Grid::parse("\
+EEEEE
+EXXXX
+EEEEE
+EXXXX
+EEEEE").unwrap();
+        let regions = grid.regions(Neighbourhood::Touching, same_value);
+        let e = regions.get(&Coord::new(0, 0));
+        assert_eq!((e.area, e.sides), (17, 12));
+
+        let grid: Grid<char> = Grid::parse("\
+AAAAAA
+AAABBA
+AAABBA
+ABBAAA
+ABBAAA
+AAAAAA").unwrap();
+        let regions = grid.regions(Neighbourhood::Touching, same_value);
+        assert_eq!(regions.get(&Coord::new(0, 0)).sides, 12);
+    }
+}
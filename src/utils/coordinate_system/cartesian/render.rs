@@ -0,0 +1,193 @@
+use std::collections::HashMap;
+use std::io;
+
+use owo_colors::{OwoColorize, Style};
+
+use super::cell::ToCell;
+use super::coord::Coord;
+use super::grid::Grid;
+
+type GlyphFn<'a, V> = Box<dyn Fn(&V) -> String + 'a>;
+type StyleFn<'a, V> = Box<dyn Fn(&V) -> Option<Style> + 'a>;
+
+/// Highlighted cells, drawn with the glyph (or the cell's own glyph) in
+/// the style.
+struct Layer {
+    glyph: Option<char>,
+    style: Style,
+}
+
+/// Renders a grid into text. Highlight layers are drawn over the values in
+/// the order they were added, ANSI styles are only emitted in ANSI mode.
+pub struct GridRenderer<'a, V> {
+    grid: &'a Grid<V>,
+    glyph: GlyphFn<'a, V>,
+    value_style: Option<StyleFn<'a, V>>,
+    layers: Vec<Layer>,
+    /// Topmost layer of every highlighted cell
+    highlighted: HashMap<Coord<usize>, usize>,
+    ruler: bool,
+    viewport: (Coord<usize>, usize, usize),
+    ansi: bool,
+}
+
+impl<'a, V> GridRenderer<'a, V>
+where V: ToCell
+{
+    pub fn new(grid: &'a Grid<V>) -> Self {
+        Self::with_glyphs(grid, |v| v.to_cell().to_string())
+    }
+}
+
+impl<'a, V> GridRenderer<'a, V> {
+    /// Renderer with a custom text for the values, it should be the same
+    /// width for every value to keep the columns aligned.
+    pub fn with_glyphs(grid: &'a Grid<V>, glyph: impl Fn(&V) -> String + 'a) -> Self {
+        GridRenderer {
+            grid,
+            glyph: Box::new(glyph),
+            value_style: None,
+            layers: vec![],
+            highlighted: HashMap::new(),
+            ruler: false,
+            viewport: (Coord::new(0, 0), grid.width, grid.height),
+            ansi: false,
+        }
+    }
+
+    pub fn value_style(mut self, style: impl Fn(&V) -> Option<Style> + 'a) -> Self {
+        self.value_style = Some(Box::new(style));
+        self
+    }
+
+    /// Draws the cells with the glyph, or their own glyph when `None`.
+    pub fn highlight<'c>(
+        mut self,
+        cells: impl IntoIterator<Item = &'c Coord<usize>>,
+        glyph: Option<char>,
+        style: Style
+    ) -> Self {
+        let layer = self.layers.len();
+        for coord in cells {
+            self.highlighted.insert(*coord, layer);
+        }
+        self.layers.push(Layer { glyph, style });
+        self
+    }
+
+    /// Prints the x coordinates above and the y coordinates left of the
+    /// grid.
+    pub fn ruler(mut self, ruler: bool) -> Self {
+        self.ruler = ruler;
+        self
+    }
+
+    /// Renders only the rectangle, clipped to the grid.
+    pub fn viewport(mut self, origin: Coord<usize>, width: usize, height: usize) -> Self {
+        let origin = Coord::new(origin.x.min(self.grid.width), origin.y.min(self.grid.height));
+        let width = width.min(self.grid.width - origin.x);
+        let height = height.min(self.grid.height - origin.y);
+        self.viewport = (origin, width, height);
+        self
+    }
+
+    pub fn ansi(mut self, ansi: bool) -> Self {
+        self.ansi = ansi;
+        self
+    }
+
+    pub fn render(&self) -> String {
+        let mut buffer = vec![];
+        self.write_to(&mut buffer).expect("Writing to a Vec can't fail");
+        String::from_utf8(buffer).expect("Rendered grid is valid UTF-8")
+    }
+
+    pub fn write_to(&self, writer: &mut impl io::Write) -> io::Result<()> {
+        let (origin, width, height) = self.viewport;
+        let label_width = if self.ruler {
+            (origin.y + height).saturating_sub(1).to_string().len()
+        } else {
+            0
+        };
+        if self.ruler && width > 0 {
+            let digits = (origin.x + width - 1).to_string().len();
+            for digit in (0..digits).rev() {
+                let line: String = (origin.x..origin.x + width).map(|x| {
+                    let power = 10usize.pow(digit as u32);
+                    if digit == 0 || x >= power {
+                        char::from_digit((x / power % 10) as u32, 10).unwrap()
+                    } else {
+                        ' '
+                    }
+                }).collect();
+                writeln!(writer, "{:label_width$} {}", "", line, label_width = label_width)?;
+            }
+        }
+        for y in origin.y..origin.y + height {
+            if self.ruler {
+                write!(writer, "{:>label_width$} ", y, label_width = label_width)?;
+            }
+            for x in origin.x..origin.x + width {
+                let coord = Coord::new(x, y);
+                let value = self.grid.get_val(&coord);
+                let (glyph, style) = match self.highlighted.get(&coord).map(|l| &self.layers[*l]) {
+                    Some(layer) => (
+                        layer.glyph.map_or_else(|| (self.glyph)(value), |g| g.to_string()),
+                        Some(layer.style)
+                    ),
+                    None => ((self.glyph)(value), self.value_style.as_ref().and_then(|s| s(value))),
+                };
+                match style {
+                    Some(style) if self.ansi => write!(writer, "{}", glyph.style(style))?,
+                    _ => write!(writer, "{}", glyph)?,
+                }
+            }
+            writeln!(writer)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid() -> Grid<char> {
+        Grid::parse("\
+#........#..
+.#..........
+..#.........").unwrap()
+    }
+
+    #[test]
+    fn test_plain() {
+        let grid = grid();
+        let path = [Coord::new(1, 0), Coord::new(2, 0)];
+        let rendered = GridRenderer::new(&grid)
+            .highlight(&path, Some('o'), Style::new().red())
+            .highlight(&[Coord::new(2, 0)], Some('X'), Style::new().green())
+            .render();
+        assert_eq!(rendered, "#oX......#..\n.#..........\n..#.........\n");
+    }
+
+    #[test]
+    fn test_ruler_and_viewport() {
+        let grid = grid();
+        let rendered = GridRenderer::new(&grid).ruler(true).viewport(Coord::new(8, 1), 10, 5).render();
+        assert_eq!(rendered, "    11\n  8901\n1 ....\n2 ....\n");
+    }
+
+    #[test]
+    fn test_ansi() {
+        let grid = grid();
+        let renderer = || GridRenderer::new(&grid)
+            .value_style(|v| (*v == '#').then(|| Style::new().bold()))
+            .viewport(Coord::new(0, 0), 2, 1);
+        assert_eq!(renderer().render(), "#.\n");
+        assert_eq!(renderer().ansi(true).render(), format!("{}.\n", "#".style(Style::new().bold())));
+
+        let mut out = vec![];
+        renderer().ansi(true).write_to(&mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), renderer().ansi(true).render());
+    }
+}
@@ -0,0 +1,360 @@
+use super::coord::Coord;
+use super::direction::{DIRECTIONS, TOUCHING_DIRECTIONS};
+use super::grid::Grid;
+use super::neighbourhood::Neighbourhood;
+use super::orientation::Oriented;
+
+impl<V> Grid<V> {
+    /// Inserts the row before row `y`, `y == height` appends it. The first
+    /// row of an empty grid sets the width.
+    pub fn insert_row(&mut self, y: usize, row: Vec<V>) {
+        assert!(y <= self.height, "Row {} is out of the grid (height {})", y, self.height);
+        if self.height == 0 {
+            self.width = row.len();
+        }
+        assert_eq!(row.len(), self.width, "Row width is not the same as the grid's");
+        self.map.splice(y * self.width..y * self.width, row);
+        self.height += 1;
+    }
+
+    pub fn remove_row(&mut self, y: usize) -> Vec<V> {
+        assert!(y < self.height, "Row {} is out of the grid (height {})", y, self.height);
+        let row = self.map.drain(y * self.width..(y + 1) * self.width).collect();
+        self.height -= 1;
+        row
+    }
+
+    /// Inserts the column before column `x`, `x == width` appends it.
+    pub fn insert_col(&mut self, x: usize, col: Vec<V>) {
+        assert!(x <= self.width, "Column {} is out of the grid (width {})", x, self.width);
+        if self.width == 0 {
+            self.height = col.len();
+        }
+        assert_eq!(col.len(), self.height, "Column height is not the same as the grid's");
+        let mut old = std::mem::take(&mut self.map).into_iter();
+        let mut map = Vec::with_capacity((self.width + 1) * self.height);
+        for value in col {
+            map.extend(old.by_ref().take(x));
+            map.push(value);
+            map.extend(old.by_ref().take(self.width - x));
+        }
+        self.map = map;
+        self.width += 1;
+    }
+
+    pub fn remove_col(&mut self, x: usize) -> Vec<V> {
+        assert!(x < self.width, "Column {} is out of the grid (width {})", x, self.width);
+        let mut col = Vec::with_capacity(self.height);
+        let mut map = Vec::with_capacity((self.width - 1) * self.height);
+        for (i, value) in std::mem::take(&mut self.map).into_iter().enumerate() {
+            if i % self.width == x {
+                col.push(value);
+            } else {
+                map.push(value);
+            }
+        }
+        self.map = map;
+        self.width -= 1;
+        col
+    }
+
+    /// Keeps only the `width` x `height` rectangle at `origin`.
+    pub fn crop(&mut self, origin: Coord<usize>, width: usize, height: usize) {
+        self.check_rect(&origin, width, height);
+        let stride = self.width;
+        let mut i = 0;
+        self.map.retain(|_| {
+            let (x, y) = (i % stride, i / stride);
+            i += 1;
+            x >= origin.x && x < origin.x + width && y >= origin.y && y < origin.y + height
+        });
+        self.width = width;
+        self.height = height;
+    }
+
+    /// Top left and bottom right corners of the cells matching the
+    /// predicate, inclusive.
+    pub fn bounding_box(&self, predicate: impl Fn(&V) -> bool) -> Option<(Coord<usize>, Coord<usize>)> {
+        self.iter_points()
+            .filter(|point| predicate(point.value))
+            .fold(None, |bounds, point| {
+                let c = point.coord;
+                Some(match bounds {
+                    None => (c, c),
+                    Some((min, max)) => (
+                        Coord::new(min.x.min(c.x), min.y.min(c.y)),
+                        Coord::new(max.x.max(c.x), max.y.max(c.y)),
+                    ),
+                })
+            })
+    }
+
+    /// Crops to the bounding box of the matching cells and returns where
+    /// it's top left corner was. Nothing matching leaves an empty grid.
+    pub fn crop_to(&mut self, predicate: impl Fn(&V) -> bool) -> Option<Coord<usize>> {
+        match self.bounding_box(predicate) {
+            Some((min, max)) => {
+                self.crop(min, max.x - min.x + 1, max.y - min.y + 1);
+                Some(min)
+            },
+            None => {
+                self.map.clear();
+                self.width = 0;
+                self.height = 0;
+                None
+            },
+        }
+    }
+
+    /// Infinite view of the grid repeated in every direction, the grid
+    /// can't be empty.
+    pub fn tiled(&self) -> Tiled<'_, V> {
+        assert!(self.width > 0 && self.height > 0, "Can't tile an empty grid ({}x{})", self.width, self.height);
+        Tiled { grid: self }
+    }
+}
+
+impl<V> Grid<V>
+where V: Clone
+{
+    /// Adds a border of `value` around the grid, the sizes are in cells.
+    pub fn pad(&mut self, top: usize, right: usize, bottom: usize, left: usize, value: V) {
+        let width = left + self.width + right;
+        let height = top + self.height + bottom;
+        let mut map = Vec::with_capacity(width * height);
+        map.resize(top * width, value.clone());
+        for row in self.map.chunks(self.width.max(1)).take(self.height) {
+            map.extend(std::iter::repeat_n(value.clone(), left));
+            map.extend_from_slice(row);
+            map.extend(std::iter::repeat_n(value.clone(), right));
+        }
+        map.resize(width * height, value);
+        self.map = map;
+        self.width = width;
+        self.height = height;
+    }
+
+    /// Grid of `tiles_x` x `tiles_y` copies of this one.
+    pub fn repeated(&self, tiles_x: usize, tiles_y: usize) -> Self {
+        let mut map = Vec::with_capacity(self.map.len() * tiles_x * tiles_y);
+        for _ in 0..tiles_y {
+            for row in self.map.chunks(self.width.max(1)) {
+                for _ in 0..tiles_x {
+                    map.extend_from_slice(row);
+                }
+            }
+        }
+        Grid { map, width: self.width * tiles_x, height: self.height * tiles_y }
+    }
+
+    /// Surrounds the grid with a one cell border of the sentinel, so every
+    /// original cell has all eight neighbours.
+    pub fn bordered(mut self, sentinel: V) -> Bordered<V> {
+        self.pad(1, 1, 1, 1, sentinel);
+        let width = self.width as isize;
+        let offsets = DIRECTIONS.map(|direction| {
+            let step: Coord<isize> = Grid::<V>::unit_step(&direction);
+            step.y * width + step.x
+        });
+        let touching = TOUCHING_DIRECTIONS.map(|direction| {
+            offsets[DIRECTIONS.iter().position(|d| *d == direction).unwrap()]
+        });
+        Bordered { grid: self, offsets, touching }
+    }
+}
+
+/// The grid repeated infinitely, indexed with signed coordinates. The tile
+/// at `(0, 0)` is the grid itself.
+#[derive(Debug, Clone, Copy)]
+pub struct Tiled<'a, V> {
+    grid: &'a Grid<V>,
+}
+
+impl<'a, V> Tiled<'a, V> {
+    pub fn get_val(&self, coord: &Coord<isize>) -> &'a V {
+        self.grid.get_val(&self.to_grid_coord(coord))
+    }
+
+    /// Matching cell of the grid.
+    pub fn to_grid_coord(&self, coord: &Coord<isize>) -> Coord<usize> {
+        Coord::new(
+            coord.x.rem_euclid(self.grid.width as isize) as usize,
+            coord.y.rem_euclid(self.grid.height as isize) as usize,
+        )
+    }
+
+    /// Which copy of the grid the coordinate is in.
+    pub fn tile(&self, coord: &Coord<isize>) -> Coord<isize> {
+        Coord::new(
+            coord.x.div_euclid(self.grid.width as isize),
+            coord.y.div_euclid(self.grid.height as isize),
+        )
+    }
+
+    /// Every cell has all the neighbours, wrapping neighbourhoods are the
+    /// same as the plain ones.
+    pub fn neighbours(&self, coord: Coord<isize>, neighbourhood: Neighbourhood) -> impl Iterator<Item = Coord<isize>> {
+        neighbourhood.directions().iter().map(move |direction| {
+            let step: Coord<isize> = Grid::<V>::unit_step(direction);
+            Coord::new(coord.x + step.x, coord.y + step.y)
+        })
+    }
+}
+
+/// Grid with a sentinel border, see [`Grid::bordered`]. Neighbours of the
+/// inner cells are found by their index without any bounds checks.
+#[derive(Debug, Clone)]
+pub struct Bordered<V> {
+    grid: Grid<V>,
+    /// Index offsets in the order of [`DIRECTIONS`]
+    offsets: [isize; 8],
+    touching: [isize; 4],
+}
+
+impl<V> Bordered<V> {
+    /// The padded grid, the original cells start at `(1, 1)`.
+    pub fn grid(&self) -> &Grid<V> {
+        &self.grid
+    }
+
+    pub fn get(&self, index: usize) -> &V {
+        &self.grid.map[index]
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> &mut V {
+        &mut self.grid.map[index]
+    }
+
+    /// Index of a cell of the original grid.
+    pub fn index_of(&self, coord: &Coord<usize>) -> usize {
+        (coord.y + 1) * self.grid.width + coord.x + 1
+    }
+
+    /// Coordinate in the original grid of an inner index.
+    pub fn coord_of(&self, index: usize) -> Coord<usize> {
+        Coord::new(index % self.grid.width - 1, index / self.grid.width - 1)
+    }
+
+    /// Indices of the original cells in row order.
+    pub fn inner_indices(&self) -> impl Iterator<Item = usize> + '_ {
+        let width = self.grid.width;
+        (1..self.grid.height.saturating_sub(1))
+            .flat_map(move |y| y * width + 1..(y + 1) * width - 1)
+    }
+
+    /// Neighbours of an inner index, border cells included. Only plain
+    /// neighbourhoods make sense here, wrapping ones panic.
+    pub fn neighbours(&self, index: usize, neighbourhood: Neighbourhood) -> impl Iterator<Item = usize> + '_ {
+        let offsets: &[isize] = match neighbourhood {
+            Neighbourhood::Touching => &self.touching,
+            Neighbourhood::Adjacent => &self.offsets,
+            _ => panic!("Bordered grids don't wrap, {:?} is not supported", neighbourhood),
+        };
+        offsets.iter().map(move |offset| index.wrapping_add_signed(*offset))
+    }
+
+    /// Original grid without the border.
+    pub fn into_inner(mut self) -> Grid<V> {
+        let (width, height) = (self.grid.width - 2, self.grid.height - 2);
+        self.grid.crop(Coord::new(1, 1), width, height);
+        self.grid
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid() -> Grid<u8> {
+        Grid::new(vec![vec![1, 2, 3], vec![4, 5, 6]])
+    }
+
+    #[test]
+    fn test_insert_and_remove() {
+        let mut grid = grid();
+        grid.insert_row(1, vec![7, 8, 9]);
+        grid.insert_col(3, vec![0, 0, 0]);
+        grid.insert_col(0, vec![10, 11, 12]);
+        assert_eq!((grid.width, grid.height), (5, 3));
+        assert_eq!(grid.map, vec![10, 1, 2, 3, 0, 11, 7, 8, 9, 0, 12, 4, 5, 6, 0]);
+
+        assert_eq!(grid.remove_col(0), vec![10, 11, 12]);
+        assert_eq!(grid.remove_col(3), vec![0, 0, 0]);
+        assert_eq!(grid.remove_row(1), vec![7, 8, 9]);
+        assert_eq!(grid, self::grid());
+
+        let mut empty: Grid<u8> = Grid::new(vec![]);
+        empty.insert_row(0, vec![1, 2]);
+        empty.insert_row(0, vec![3, 4]);
+        assert_eq!(empty.map, vec![3, 4, 1, 2]);
+        assert_eq!(empty.remove_col(0), vec![3, 1]);
+        assert_eq!((empty.width, empty.height), (1, 2));
+    }
+
+    #[test]
+    fn test_pad_and_crop() {
+        let mut grid = grid();
+        grid.pad(1, 0, 2, 1, 0);
+        assert_eq!((grid.width, grid.height), (4, 5));
+        assert_eq!(grid.to_string(), "0000\n0123\n0456\n0000\n0000\n");
+
+        assert_eq!(grid.bounding_box(|v| *v == 2 || *v == 4), Some((Coord::new(1, 1), Coord::new(2, 2))));
+        assert_eq!(grid.crop_to(|v| *v != 0), Some(Coord::new(1, 1)));
+        assert_eq!(grid, self::grid());
+        grid.crop(Coord::new(1, 0), 2, 1);
+        assert_eq!(grid.map, vec![2, 3]);
+        assert_eq!(grid.crop_to(|v| *v > 10), None);
+        assert_eq!((grid.width, grid.height, grid.map.len()), (0, 0, 0));
+    }
+
+    #[test]
+    fn test_tiled() {
+        let grid = grid();
+        let tiled = grid.tiled();
+        assert_eq!(*tiled.get_val(&Coord::new(-1, -1)), 6);
+        assert_eq!(*tiled.get_val(&Coord::new(4, 5)), 5);
+        assert_eq!(tiled.tile(&Coord::new(-1, 4)), Coord::new(-1, 2));
+        let neighbours: Vec<_> = tiled.neighbours(Coord::new(0, 0), Neighbourhood::Touching).collect();
+        assert_eq!(neighbours, vec![Coord::new(0, -1), Coord::new(1, 0), Coord::new(0, 1), Coord::new(-1, 0)]);
+
+        let repeated = grid.repeated(2, 3);
+        assert_eq!((repeated.width, repeated.height), (6, 6));
+        for coord in repeated.iter_coords() {
+            let signed = Coord::new(coord.x as isize, coord.y as isize);
+            assert_eq!(repeated.get_val(&coord), tiled.get_val(&signed));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Can't tile an empty grid")]
+    fn test_tiled_empty() {
+        let grid: Grid<u8> = Grid { map: vec![], width: 0, height: 3 };
+        grid.tiled();
+    }
+
+    #[test]
+    fn test_bordered() {
+        let grid = grid();
+        let bordered = grid.clone().bordered(0);
+        assert_eq!((bordered.grid().width, bordered.grid().height), (5, 4));
+
+        for neighbourhood in [Neighbourhood::Touching, Neighbourhood::Adjacent] {
+            for index in bordered.inner_indices() {
+                let coord = bordered.coord_of(index);
+                assert_eq!(bordered.index_of(&coord), index);
+                let mut from_border: Vec<u8> = bordered.neighbours(index, neighbourhood)
+                    .map(|i| *bordered.get(i))
+                    .filter(|v| *v != 0)
+                    .collect();
+                let mut from_grid: Vec<u8> = grid.neighbours(coord, neighbourhood)
+                    .map(|c| *grid.get_val(&c))
+                    .collect();
+                from_border.sort();
+                from_grid.sort();
+                assert_eq!(from_border, from_grid);
+            }
+        }
+        assert_eq!(bordered.inner_indices().count(), 6);
+        assert_eq!(bordered.into_inner(), grid);
+    }
+}
@@ -0,0 +1,251 @@
+use std::collections::HashSet;
+
+use super::coord::Coord;
+use super::direction::Direction;
+use super::grid::Grid;
+use super::orientation::Oriented;
+use super::point::Point;
+
+/// Which way a ray goes from it's start, steps are in grid coordinates
+/// (y grows downwards).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Ray {
+    Direction(Direction),
+    /// Only the cells exactly on the line, multiples of the reduced step
+    /// (e.g. `(2, 4)` visits every `(1, 2)`)
+    Lattice(isize, isize),
+    /// Every cell the line passes through, drawn with Bresenham's algorithm
+    Line(isize, isize),
+}
+
+impl Ray {
+    /// Same ray with the step reduced to the smallest one.
+    fn normalized(&self) -> Ray {
+        match *self {
+            Ray::Direction(direction) => {
+                let offset: Coord<isize> = Grid::<()>::unit_step(&direction);
+                Ray::Lattice(offset.x, offset.y)
+            },
+            Ray::Lattice(dx, dy) | Ray::Line(dx, dy) => {
+                assert!(dx != 0 || dy != 0, "Ray {:?} has no direction", self);
+                let divisor = num::integer::gcd(dx, dy);
+                match self {
+                    Ray::Lattice(..) => Ray::Lattice(dx / divisor, dy / divisor),
+                    _ => Ray::Line(dx / divisor, dy / divisor),
+                }
+            },
+        }
+    }
+}
+
+/// Cells of a ray up to the edge of the grid, the start is not included.
+pub struct RayIterator {
+    width: usize,
+    height: usize,
+    position: (isize, isize),
+    ray: Ray,
+    /// Bresenham's error term of `Ray::Line`
+    error: isize,
+}
+
+impl Iterator for RayIterator {
+    type Item = Coord<usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (x, y) = &mut self.position;
+        match self.ray {
+            Ray::Lattice(dx, dy) => {
+                *x += dx;
+                *y += dy;
+            },
+            Ray::Line(dx, dy) => {
+                let (ax, ay) = (dx.abs(), -dy.abs());
+                let doubled = 2 * self.error;
+                if doubled >= ay {
+                    self.error += ay;
+                    *x += dx.signum();
+                }
+                if doubled <= ax {
+                    self.error += ax;
+                    *y += dy.signum();
+                }
+            },
+            Ray::Direction(_) => unreachable!("Rays are normalized to steps"),
+        }
+        if *x < 0 || *y < 0 || *x as usize >= self.width || *y as usize >= self.height {
+            return None;
+        }
+        Some(Coord::new(*x as usize, *y as usize))
+    }
+}
+
+impl<V> Grid<V> {
+    /// Like [`Grid::direction_iter`] for any kind of ray, the arguments are
+    /// in the same order.
+    pub fn ray_iter(&self, ray: Ray, from: Coord<usize>) -> RayIterator {
+        let ray = ray.normalized();
+        let error = match ray {
+            Ray::Line(dx, dy) => dx.abs() - dy.abs(),
+            _ => 0,
+        };
+        RayIterator {
+            width: self.width,
+            height: self.height,
+            position: (from.x as isize, from.y as isize),
+            ray,
+            error,
+        }
+    }
+
+    /// First cell of the ray that blocks the sight.
+    pub fn first_hit(&self, ray: Ray, from: Coord<usize>, blocks: impl Fn(&V) -> bool) -> Option<Point<usize, &V>> {
+        self.ray_iter(ray, from).map(|c| self.get_point(&c)).find(|p| blocks(p.value))
+    }
+
+    /// Cells seen along the ray, up to and including the first blocking
+    /// one.
+    pub fn visible_along(&self, ray: Ray, from: Coord<usize>, blocks: impl Fn(&V) -> bool) -> Vec<Coord<usize>> {
+        let mut visible = vec![];
+        for coord in self.ray_iter(ray, from) {
+            visible.push(coord);
+            if blocks(self.get_val(&coord)) {
+                break;
+            }
+        }
+        visible
+    }
+
+    /// Every cell seen from the cell. Sight goes along exact lattice lines,
+    /// so a blocking cell only hides the cells directly behind it.
+    pub fn visible_cells(&self, from: Coord<usize>, blocks: impl Fn(&V) -> bool) -> Vec<Coord<usize>> {
+        let mut rays = HashSet::new();
+        let mut visible = vec![];
+        for coord in self.iter_coords().filter(|c| *c != from) {
+            let (dx, dy) = (coord.x as isize - from.x as isize, coord.y as isize - from.y as isize);
+            let divisor = num::integer::gcd(dx, dy);
+            if rays.insert((dx / divisor, dy / divisor)) {
+                visible.extend(self.visible_along(Ray::Lattice(dx, dy), from, &blocks));
+            }
+        }
+        visible
+    }
+
+    /// Whether no cell on the Bresenham line strictly between the cells
+    /// blocks the sight.
+    pub fn line_of_sight(&self, from: Coord<usize>, to: Coord<usize>, blocks: impl Fn(&V) -> bool) -> bool {
+        if from == to {
+            return true;
+        }
+        let ray = Ray::Line(to.x as isize - from.x as isize, to.y as isize - from.y as isize);
+        self.ray_iter(ray, from)
+            .take_while(|c| *c != to)
+            .all(|c| !blocks(self.get_val(&c)))
+    }
+
+    /// Cells seen when looking into the grid from the `side` edge along
+    /// every row or column. A cell is seen when it's height is above every
+    /// cell in front of it, like trees in a forest.
+    pub fn visible_from_side<H: Ord>(&self, side: Direction, height: impl Fn(&V) -> H) -> Grid<bool> {
+        let mut visible = Grid { map: vec![false; self.map.len()], width: self.width, height: self.height };
+        let (starts, inwards): (Vec<Coord<usize>>, Direction) = match side {
+            Direction::N => ((0..self.width).map(|x| Coord::new(x, 0)).collect(), Direction::S),
+            Direction::S => ((0..self.width).map(|x| Coord::new(x, self.height.saturating_sub(1))).collect(), Direction::N),
+            Direction::W => ((0..self.height).map(|y| Coord::new(0, y)).collect(), Direction::E),
+            Direction::E => ((0..self.height).map(|y| Coord::new(self.width.saturating_sub(1), y)).collect(), Direction::W),
+            _ => panic!("Grids are only seen from the sides N, E, S and W, not {:?}", side),
+        };
+        if self.map.is_empty() {
+            return visible;
+        }
+        for start in starts {
+            let mut tallest = height(self.get_val(&start));
+            *visible.get_val_mut(&start) = true;
+            for coord in self.direction_iter(inwards, start) {
+                let current = height(self.get_val(&coord));
+                if current > tallest {
+                    *visible.get_val_mut(&coord) = true;
+                    tallest = current;
+                }
+            }
+        }
+        visible
+    }
+
+    /// Cells seen from at least one of the four sides.
+    pub fn visible_from_outside<H: Ord>(&self, height: impl Fn(&V) -> H) -> Grid<bool> {
+        let mut visible = self.visible_from_side(Direction::N, &height);
+        for side in [Direction::E, Direction::S, Direction::W] {
+            let seen = self.visible_from_side(side, &height);
+            visible.map.iter_mut().zip(seen.map).for_each(|(v, s)| *v |= s);
+        }
+        visible
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::direction::DIRECTIONS;
+
+    #[test]
+    fn test_rays() {
+        let grid: Grid<char> = Grid::parse("\
+.....
+.#...
+.....
+...#.
+.....").unwrap();
+        let wall = |c: &char| *c == '#';
+        let from = Coord::new(0, 0);
+        assert_eq!(grid.first_hit(Ray::Direction(Direction::SE), from, wall).map(|p| p.coord), Some(Coord::new(1, 1)));
+        assert!(grid.first_hit(Ray::Direction(Direction::E), from, wall).is_none());
+        assert_eq!(grid.visible_along(Ray::Direction(Direction::NW), Coord::new(4, 4), wall), vec![Coord::new(3, 3)]);
+
+        for direction in DIRECTIONS {
+            let by_ray: Vec<_> = grid.ray_iter(Ray::Direction(direction), Coord::new(2, 1)).collect();
+            assert_eq!(by_ray, grid.direction_iter(direction, Coord::new(2, 1)).collect::<Vec<_>>());
+        }
+
+        let lattice: Vec<_> = grid.ray_iter(Ray::Lattice(2, 1), Coord::new(0, 1)).collect();
+        assert_eq!(lattice, vec![Coord::new(2, 2), Coord::new(4, 3)]);
+        let line: Vec<_> = grid.ray_iter(Ray::Line(4, 2), Coord::new(0, 1)).collect();
+        assert_eq!(line, vec![Coord::new(1, 2), Coord::new(2, 2), Coord::new(3, 3), Coord::new(4, 3)]);
+
+        assert!(!grid.line_of_sight(Coord::new(0, 1), Coord::new(2, 1), wall));
+        assert!(grid.line_of_sight(Coord::new(0, 1), Coord::new(1, 1), wall));
+        assert!(grid.line_of_sight(Coord::new(0, 4), Coord::new(4, 0), wall));
+        assert!(!grid.line_of_sight(Coord::new(4, 2), Coord::new(2, 4), wall));
+    }
+
+    #[test]
+    fn test_visible_cells() {
+        let grid: Grid<char> = Grid::parse("\
+.....
+.#...
+.....
+...#.
+.....").unwrap();
+        let visible = grid.visible_cells(Coord::new(0, 0), |c| *c == '#');
+        assert_eq!(visible.len(), 24 - 3);
+        for hidden in [Coord::new(2, 2), Coord::new(3, 3), Coord::new(4, 4)] {
+            assert!(!visible.contains(&hidden), "{}", hidden);
+        }
+        assert!(visible.contains(&Coord::new(1, 1)));
+    }
+
+    #[test]
+    fn test_visible_from_outside() {
+        let forest: Grid<u8> = Grid::parse("\
+30373
+25512
+65332
+33549
+35390").unwrap();
+        let visible = forest.visible_from_outside(|h| *h);
+        assert_eq!(visible.iter_values().filter(|v| **v).count(), 21);
+        assert!(*visible.get_val(&Coord::new(1, 1)));
+        assert!(!*visible.get_val(&Coord::new(3, 1)));
+        let from_top = forest.visible_from_side(Direction::N, |h| *h);
+        assert_eq!(from_top.row(1), &[false, true, true, false, false]);
+    }
+}
@@ -0,0 +1,224 @@
+use std::mem;
+
+use super::grid::Grid;
+
+/// Symmetries of a rectangle, the dihedral group D4. Rotations are
+/// clockwise, flips mirror the grid over it's middle line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Symmetry {
+    Identity,
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    /// Mirrors left and right
+    FlipHorizontal,
+    /// Mirrors top and bottom
+    FlipVertical,
+    /// Mirrors over the main diagonal, top left to bottom right
+    Transpose,
+    /// Mirrors over the anti diagonal, top right to bottom left
+    AntiTranspose,
+}
+
+pub const SYMMETRIES: [Symmetry; 8] = [
+    Symmetry::Identity, Symmetry::Rotate90, Symmetry::Rotate180, Symmetry::Rotate270,
+    Symmetry::FlipHorizontal, Symmetry::FlipVertical, Symmetry::Transpose, Symmetry::AntiTranspose,
+];
+
+impl Symmetry {
+    pub fn inverse(&self) -> Self {
+        match self {
+            Symmetry::Rotate90 => Symmetry::Rotate270,
+            Symmetry::Rotate270 => Symmetry::Rotate90,
+            other => *other,
+        }
+    }
+
+    /// Whether width and height trade places.
+    pub fn swaps_dimensions(&self) -> bool {
+        matches!(self, Symmetry::Rotate90 | Symmetry::Rotate270 | Symmetry::Transpose | Symmetry::AntiTranspose)
+    }
+
+    /// Coordinate in the original `width` x `height` grid of the value that
+    /// ends up on `(x, y)` after the transformation.
+    pub fn source(&self, x: usize, y: usize, width: usize, height: usize) -> (usize, usize) {
+        match self {
+            Symmetry::Identity => (x, y),
+            Symmetry::Rotate90 => (y, height - 1 - x),
+            Symmetry::Rotate180 => (width - 1 - x, height - 1 - y),
+            Symmetry::Rotate270 => (width - 1 - y, x),
+            Symmetry::FlipHorizontal => (width - 1 - x, y),
+            Symmetry::FlipVertical => (x, height - 1 - y),
+            Symmetry::Transpose => (y, x),
+            Symmetry::AntiTranspose => (width - 1 - y, height - 1 - x),
+        }
+    }
+
+    fn dimensions(&self, width: usize, height: usize) -> (usize, usize) {
+        if self.swaps_dimensions() { (height, width) } else { (width, height) }
+    }
+}
+
+impl<V> Grid<V> {
+    /// Applies the symmetry, square grids are transformed in place by
+    /// swapping values, the rest are moved into a new buffer.
+    pub fn transform(&mut self, symmetry: Symmetry) {
+        if self.width != self.height {
+            let (width, height) = symmetry.dimensions(self.width, self.height);
+            let mut values: Vec<Option<V>> = mem::take(&mut self.map).into_iter().map(Some).collect();
+            self.map = (0..height).flat_map(|y| (0..width).map(move |x| (x, y))).map(|(x, y)| {
+                let (sx, sy) = symmetry.source(x, y, self.width, self.height);
+                values[sy * self.width + sx].take().unwrap()
+            }).collect();
+            self.width = width;
+            self.height = height;
+            return;
+        }
+        match symmetry {
+            Symmetry::Identity => {},
+            Symmetry::Rotate90 => { self.transpose_square(); self.flip_horizontal(); },
+            Symmetry::Rotate180 => { self.flip_horizontal(); self.flip_vertical(); },
+            Symmetry::Rotate270 => { self.transpose_square(); self.flip_vertical(); },
+            Symmetry::FlipHorizontal => self.flip_horizontal(),
+            Symmetry::FlipVertical => self.flip_vertical(),
+            Symmetry::Transpose => self.transpose_square(),
+            Symmetry::AntiTranspose => { self.flip_horizontal(); self.flip_vertical(); self.transpose_square(); },
+        }
+    }
+
+    pub fn rotate(&mut self, clockwise: bool) {
+        self.transform(if clockwise { Symmetry::Rotate90 } else { Symmetry::Rotate270 });
+    }
+
+    pub fn rotate_180(&mut self) {
+        self.flip_horizontal();
+        self.flip_vertical();
+    }
+
+    pub fn transpose(&mut self) {
+        self.transform(Symmetry::Transpose);
+    }
+
+    pub fn flip_horizontal(&mut self) {
+        if self.width == 0 {
+            return;
+        }
+        for row in self.map.chunks_mut(self.width) {
+            row.reverse();
+        }
+    }
+
+    pub fn flip_vertical(&mut self) {
+        for y in 0..self.height / 2 {
+            let (top, bottom) = self.map.split_at_mut((self.height - 1 - y) * self.width);
+            top[y * self.width..(y + 1) * self.width].swap_with_slice(&mut bottom[..self.width]);
+        }
+    }
+
+    fn transpose_square(&mut self) {
+        for y in 0..self.height {
+            for x in y + 1..self.width {
+                self.map.swap(y * self.width + x, x * self.width + y);
+            }
+        }
+    }
+}
+
+impl<V> Grid<V>
+where V: Clone
+{
+    pub fn transformed(&self, symmetry: Symmetry) -> Grid<V> {
+        let (width, height) = symmetry.dimensions(self.width, self.height);
+        let map = (0..height).flat_map(|y| (0..width).map(move |x| (x, y))).map(|(x, y)| {
+            let (sx, sy) = symmetry.source(x, y, self.width, self.height);
+            self.map[sy * self.width + sx].clone()
+        }).collect();
+        Grid { map, width, height }
+    }
+
+    /// All 8 images of the grid, in the order of [`SYMMETRIES`]. Symmetric
+    /// grids produce some of the images more than once.
+    pub fn symmetries(&self) -> impl Iterator<Item = (Symmetry, Grid<V>)> + '_ {
+        SYMMETRIES.iter().map(|symmetry| (*symmetry, self.transformed(*symmetry)))
+    }
+}
+
+impl<V> Grid<V>
+where V: Clone + Ord
+{
+    /// Smallest of the images of the grid, grids that are the same up to
+    /// rotation and reflection have the same canonical form.
+    pub fn canonical(&self) -> (Symmetry, Grid<V>) {
+        self.symmetries()
+            .min_by(|(_, a), (_, b)| (a.width, &a.map).cmp(&(b.width, &b.map)))
+            .unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows(grid: &Grid<char>) -> Vec<String> {
+        grid.map.chunks(grid.width).map(|row| row.iter().collect()).collect()
+    }
+
+    #[test]
+    fn test_rectangle_symmetries() {
+        let grid: Grid<char> = Grid::parse("abc\ndef").unwrap();
+        let image = |symmetry| rows(&grid.transformed(symmetry));
+        assert_eq!(image(Symmetry::Rotate90), vec!["da", "eb", "fc"]);
+        assert_eq!(image(Symmetry::Rotate180), vec!["fed", "cba"]);
+        assert_eq!(image(Symmetry::Rotate270), vec!["cf", "be", "ad"]);
+        assert_eq!(image(Symmetry::FlipHorizontal), vec!["cba", "fed"]);
+        assert_eq!(image(Symmetry::FlipVertical), vec!["def", "abc"]);
+        assert_eq!(image(Symmetry::Transpose), vec!["ad", "be", "cf"]);
+        assert_eq!(image(Symmetry::AntiTranspose), vec!["fc", "eb", "da"]);
+
+        for symmetry in SYMMETRIES {
+            let mut moved = grid.clone();
+            moved.transform(symmetry);
+            assert_eq!(rows(&moved), image(symmetry));
+            moved.transform(symmetry.inverse());
+            assert_eq!(rows(&moved), rows(&grid));
+        }
+    }
+
+    #[test]
+    fn test_square_in_place() {
+        let grid: Grid<char> = Grid::parse("abc\ndef\nghi").unwrap();
+        for symmetry in SYMMETRIES {
+            let mut in_place = grid.clone();
+            in_place.transform(symmetry);
+            assert_eq!(rows(&in_place), rows(&grid.transformed(symmetry)), "{:?}", symmetry);
+        }
+        let mut rotated = grid.clone();
+        rotated.rotate(true);
+        assert_eq!(rows(&rotated), vec!["gda", "heb", "ifc"]);
+    }
+
+    #[test]
+    fn test_non_copy_values() {
+        let mut grid = Grid::new(vec![
+            vec![String::from("a"), String::from("b")],
+            vec![String::from("c"), String::from("d")],
+            vec![String::from("e"), String::from("f")],
+        ]);
+        grid.rotate(false);
+        assert_eq!(grid.map, vec!["b", "d", "f", "a", "c", "e"]);
+        assert_eq!((grid.width, grid.height), (3, 2));
+    }
+
+    #[test]
+    fn test_canonical() {
+        let grid: Grid<char> = Grid::parse("#..\n##.").unwrap();
+        let (_, canonical) = grid.canonical();
+        for (_, image) in grid.symmetries() {
+            assert_eq!(rows(&image.canonical().1), rows(&canonical));
+        }
+        let other: Grid<char> = Grid::parse("##.\n#..").unwrap();
+        assert_eq!(rows(&other.canonical().1), rows(&canonical));
+        let different: Grid<char> = Grid::parse("#.#\n##.").unwrap();
+        assert_ne!(rows(&different.canonical().1), rows(&canonical));
+    }
+}
@@ -0,0 +1,276 @@
+use std::fmt;
+
+use super::coord::Coord;
+use super::grid::Grid;
+use super::point::Point;
+
+impl<V> Grid<V> {
+    pub fn row(&self, y: usize) -> &[V] {
+        &self.map[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn row_mut(&mut self, y: usize) -> &mut [V] {
+        &mut self.map[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn col(&self, x: usize) -> impl DoubleEndedIterator<Item = &V> + ExactSizeIterator {
+        assert!(x < self.width, "Column {} is out of the grid (width {})", x, self.width);
+        self.map.iter().skip(x).step_by(self.width)
+    }
+
+    pub fn col_mut(&mut self, x: usize) -> impl Iterator<Item = &mut V> {
+        assert!(x < self.width, "Column {} is out of the grid (width {})", x, self.width);
+        self.map.iter_mut().skip(x).step_by(self.width)
+    }
+
+    pub fn rows(&self) -> impl DoubleEndedIterator<Item = &[V]> + ExactSizeIterator {
+        self.map.chunks(self.width.max(1))
+    }
+
+    pub fn rows_mut(&mut self) -> impl DoubleEndedIterator<Item = &mut [V]> + ExactSizeIterator {
+        self.map.chunks_mut(self.width.max(1))
+    }
+
+    pub fn cols(&self) -> impl DoubleEndedIterator<Item = impl DoubleEndedIterator<Item = &V> + ExactSizeIterator> + ExactSizeIterator {
+        (0..self.width).map(|x| self.col(x))
+    }
+
+    /// Borrowed `width` x `height` rectangle with the top left corner at
+    /// `origin`. Coordinates of the view start at `(0, 0)`.
+    pub fn sub_grid(&self, origin: Coord<usize>, width: usize, height: usize) -> SubGrid<'_, V> {
+        self.check_rect(&origin, width, height);
+        SubGrid { map: &self.map[..], stride: self.width, origin, width, height }
+    }
+
+    pub fn sub_grid_mut(&mut self, origin: Coord<usize>, width: usize, height: usize) -> SubGridMut<'_, V> {
+        self.check_rect(&origin, width, height);
+        SubGridMut { map: &mut self.map[..], stride: self.width, origin, width, height }
+    }
+
+    pub(super) fn check_rect(&self, origin: &Coord<usize>, width: usize, height: usize) {
+        if origin.x + width > self.width || origin.y + height > self.height {
+            panic!(
+                "Sub grid {}x{} at {} is out of the grid ({}x{})",
+                width, height, origin, self.width, self.height
+            );
+        }
+    }
+}
+
+/// Rectangular view into a [`Grid`].
+#[derive(Clone, Copy)]
+pub struct SubGrid<'a, V> {
+    map: &'a [V],
+    stride: usize,
+    /// Top left corner in the viewed grid
+    pub origin: Coord<usize>,
+    pub width: usize,
+    pub height: usize,
+}
+
+/// Rectangular mutable view into a [`Grid`].
+pub struct SubGridMut<'a, V> {
+    map: &'a mut [V],
+    stride: usize,
+    /// Top left corner in the viewed grid
+    pub origin: Coord<usize>,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl<'a, V> SubGrid<'a, V> {
+    fn index(&self, coord: &Coord<usize>) -> usize {
+        assert!(
+            coord.x < self.width && coord.y < self.height,
+            "{} is out of the sub grid ({}x{})", coord, self.width, self.height
+        );
+        (self.origin.y + coord.y) * self.stride + self.origin.x + coord.x
+    }
+
+    pub fn get_val(&self, coord: &Coord<usize>) -> &'a V {
+        &self.map[self.index(coord)]
+    }
+
+    pub fn row(&self, y: usize) -> &'a [V] {
+        assert!(y < self.height, "Row {} is out of the sub grid (height {})", y, self.height);
+        let start = (self.origin.y + y) * self.stride + self.origin.x;
+        &self.map[start..start + self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &'a [V]> + '_ {
+        (0..self.height).map(|y| self.row(y))
+    }
+
+    pub fn iter_values(&self) -> impl Iterator<Item = &'a V> + '_ {
+        self.rows().flatten()
+    }
+
+    /// Points with the coordinates of the view.
+    pub fn iter_points(&self) -> impl Iterator<Item = Point<usize, &'a V>> + '_ {
+        (0..self.height).flat_map(move |y| {
+            self.row(y).iter().enumerate().map(move |(x, v)| Point::new(x, y, v))
+        })
+    }
+
+    /// Coordinate of the view in the viewed grid.
+    pub fn to_grid_coord(&self, coord: &Coord<usize>) -> Coord<usize> {
+        Coord::new(self.origin.x + coord.x, self.origin.y + coord.y)
+    }
+}
+
+impl<V> SubGrid<'_, V>
+where V: Clone
+{
+    pub fn to_grid(&self) -> Grid<V> {
+        Grid { map: self.iter_values().cloned().collect(), width: self.width, height: self.height }
+    }
+}
+
+impl<'a, V> SubGridMut<'a, V> {
+    fn index(&self, coord: &Coord<usize>) -> usize {
+        assert!(
+            coord.x < self.width && coord.y < self.height,
+            "{} is out of the sub grid ({}x{})", coord, self.width, self.height
+        );
+        (self.origin.y + coord.y) * self.stride + self.origin.x + coord.x
+    }
+
+    pub fn as_sub_grid(&self) -> SubGrid<'_, V> {
+        SubGrid { map: self.map, stride: self.stride, origin: self.origin, width: self.width, height: self.height }
+    }
+
+    pub fn get_val(&self, coord: &Coord<usize>) -> &V {
+        &self.map[self.index(coord)]
+    }
+
+    pub fn get_val_mut(&mut self, coord: &Coord<usize>) -> &mut V {
+        let index = self.index(coord);
+        &mut self.map[index]
+    }
+
+    pub fn row_mut(&mut self, y: usize) -> &mut [V] {
+        assert!(y < self.height, "Row {} is out of the sub grid (height {})", y, self.height);
+        let start = (self.origin.y + y) * self.stride + self.origin.x;
+        &mut self.map[start..start + self.width]
+    }
+
+    pub fn rows_mut(&mut self) -> impl Iterator<Item = &mut [V]> {
+        let (origin, width, height) = (self.origin, self.width, self.height);
+        self.map
+            .chunks_mut(self.stride)
+            .skip(origin.y)
+            .take(height)
+            .map(move |row| &mut row[origin.x..origin.x + width])
+    }
+
+    /// Points with the coordinates of the view.
+    pub fn iter_points_mut(&mut self) -> impl Iterator<Item = Point<usize, &mut V>> {
+        self.rows_mut().enumerate().flat_map(|(y, row)| {
+            row.iter_mut().enumerate().map(move |(x, v)| Point::new(x, y, v))
+        })
+    }
+
+    pub fn fill(&mut self, value: V)
+    where V: Clone
+    {
+        for row in self.rows_mut() {
+            row.fill(value.clone());
+        }
+    }
+}
+
+impl<V> fmt::Display for SubGrid<'_, V>
+where V: fmt::Display
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.rows() {
+            for v in row {
+                write!(f, "{}", v)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl<V> fmt::Display for SubGridMut<'_, V>
+where V: fmt::Display
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_sub_grid().fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid() -> Grid<u8> {
+        Grid::parse("0123\n4567\n8901").unwrap()
+    }
+
+    #[test]
+    fn test_rows_and_cols() {
+        let mut grid = grid();
+        assert_eq!(grid.row(1), &[4, 5, 6, 7]);
+        assert_eq!(grid.col(2).copied().collect::<Vec<_>>(), vec![2, 6, 0]);
+        assert_eq!(grid.rows().len(), 3);
+        let sums: Vec<u8> = grid.cols().map(|col| col.sum()).collect();
+        assert_eq!(sums, vec![12, 15, 8, 11]);
+
+        grid.col_mut(0).for_each(|v| *v = 0);
+        grid.row_mut(2)[3] = 9;
+        assert_eq!(grid.to_cells(), "0123\n0567\n0909\n");
+    }
+
+    #[test]
+    fn test_sub_grid() {
+        let grid = grid();
+        let view = grid.sub_grid(Coord::new(1, 1), 2, 2);
+        assert_eq!(*view.get_val(&Coord::new(0, 0)), 5);
+        assert_eq!(view.rows().collect::<Vec<_>>(), vec![&[5, 6], &[9, 0]]);
+        assert_eq!(view.to_string(), "56\n90\n");
+        assert_eq!(view.to_grid().map, vec![5, 6, 9, 0]);
+        let last = view.iter_points().last().unwrap();
+        assert_eq!((last.coord, *last.value), (Coord::new(1, 1), 0));
+        assert_eq!(view.to_grid_coord(&last.coord), Coord::new(2, 2));
+    }
+
+    #[test]
+    fn test_sub_grid_mut() {
+        let mut grid = grid();
+        let mut view = grid.sub_grid_mut(Coord::new(2, 0), 2, 3);
+        view.fill(0);
+        *view.get_val_mut(&Coord::new(1, 2)) = 7;
+        for point in view.iter_points_mut() {
+            if point.coord.y == 1 {
+                *point.value = 5;
+            }
+        }
+        assert_eq!(view.to_string(), "00\n55\n07\n");
+        assert_eq!(grid.to_cells(), "0100\n4555\n8907\n");
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_sub_grid_out_of_bounds() {
+        grid().sub_grid(Coord::new(3, 0), 2, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of the sub grid")]
+    fn test_sub_grid_cell_out_of_bounds() {
+        let grid = grid();
+        let view = grid.sub_grid(Coord::new(1, 1), 2, 2);
+        // Inside of the parent grid, but not of the view
+        view.get_val(&Coord::new(2, 0));
+    }
+
+    #[test]
+    #[should_panic(expected = "out of the sub grid")]
+    fn test_sub_grid_mut_cell_out_of_bounds() {
+        let mut grid = grid();
+        let mut view = grid.sub_grid_mut(Coord::new(0, 0), 2, 2);
+        *view.get_val_mut(&Coord::new(0, 2)) = 0;
+    }
+}
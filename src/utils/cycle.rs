@@ -0,0 +1,169 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Repeating part of a sequence of states: the state after `start` steps
+/// is the first one that repeats, `length` steps later.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    pub start: usize,
+    pub length: usize,
+}
+
+impl Cycle {
+    /// Number of steps from the initial state to a state equal to the one
+    /// after `n` steps, never more than `start + length`.
+    pub fn reduce(&self, n: usize) -> usize {
+        if n < self.start {
+            n
+        } else {
+            self.start + (n - self.start) % self.length
+        }
+    }
+}
+
+/// Brent's algorithm, keeps only two states in memory. The sequence has to
+/// repeat eventually or it never returns.
+pub fn brent<S: Eq + Clone>(initial: &S, mut step: impl FnMut(&S) -> S) -> Cycle {
+    let mut power = 1;
+    let mut length = 1;
+    let mut tortoise = initial.clone();
+    let mut hare = step(initial);
+    while tortoise != hare {
+        if power == length {
+            tortoise = hare.clone();
+            power *= 2;
+            length = 0;
+        }
+        hare = step(&hare);
+        length += 1;
+    }
+
+    let mut tortoise = initial.clone();
+    let mut hare = initial.clone();
+    for _ in 0..length {
+        hare = step(&hare);
+    }
+    let mut start = 0;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        start += 1;
+    }
+    Cycle { start, length }
+}
+
+/// Floyd's algorithm, like [`brent`] but with more steps.
+pub fn floyd<S: Eq + Clone>(initial: &S, mut step: impl FnMut(&S) -> S) -> Cycle {
+    let mut tortoise = step(initial);
+    let mut hare = step(&tortoise);
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        let next = step(&hare);
+        hare = step(&next);
+    }
+
+    let mut start = 0;
+    let mut tortoise = initial.clone();
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        start += 1;
+    }
+
+    let mut length = 1;
+    let mut hare = step(&tortoise);
+    while tortoise != hare {
+        hare = step(&hare);
+        length += 1;
+    }
+    Cycle { start, length }
+}
+
+/// Remembers every state until one repeats, each state is stepped only
+/// once. Returns the cycle and all the states before it repeats.
+pub fn hashed<S: Hash + Eq + Clone>(initial: &S, mut step: impl FnMut(&S) -> S) -> (Cycle, Vec<S>) {
+    let mut seen: HashMap<S, usize> = HashMap::new();
+    let mut states = vec![initial.clone()];
+    seen.insert(initial.clone(), 0);
+    loop {
+        let next = step(states.last().unwrap());
+        if let Some(start) = seen.get(&next) {
+            let cycle = Cycle { start: *start, length: states.len() - start };
+            return (cycle, states);
+        }
+        seen.insert(next.clone(), states.len());
+        states.push(next);
+    }
+}
+
+/// State after `n` steps. Stops simulating at the first repeated state and
+/// skips all the whole cycles after it.
+pub fn nth_state<S: Hash + Eq + Clone>(initial: &S, mut step: impl FnMut(&S) -> S, n: usize) -> S {
+    let mut seen: HashMap<S, usize> = HashMap::new();
+    let mut states = vec![initial.clone()];
+    seen.insert(initial.clone(), 0);
+    while states.len() <= n {
+        let next = step(states.last().unwrap());
+        if let Some(start) = seen.get(&next) {
+            let cycle = Cycle { start: *start, length: states.len() - start };
+            return states.swap_remove(cycle.reduce(n));
+        }
+        seen.insert(next.clone(), states.len());
+        states.push(next);
+    }
+    states.swap_remove(n)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::coordinate_system::cartesian::{Automaton, Coord, Grid, Neighbourhood, Neighbours};
+
+    #[test]
+    fn test_methods_agree() {
+        for seed in 0..50u64 {
+            let step = |x: &u64| (x * x + seed) % 1009;
+            let cycle = brent(&seed, step);
+            assert_eq!(floyd(&seed, step), cycle);
+            let (hashed_cycle, states) = hashed(&seed, step);
+            assert_eq!(hashed_cycle, cycle);
+            assert_eq!(states.len(), cycle.start + cycle.length);
+
+            for n in [0, 1, 7, 1000, 1_000_000_000] {
+                let mut expected = seed;
+                for _ in 0..cycle.reduce(n) {
+                    expected = step(&expected);
+                }
+                assert_eq!(nth_state(&seed, step, n), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_short_sequences() {
+        assert_eq!(brent(&5, |x| *x), Cycle { start: 0, length: 1 });
+        assert_eq!(floyd(&0, |x| (x + 1) % 3), Cycle { start: 0, length: 3 });
+        assert_eq!(hashed(&0, |x| (x + 1).min(4)).0, Cycle { start: 4, length: 1 });
+        // Never gets to the repetition
+        assert_eq!(nth_state(&0u64, |x| x + 1, 10), 10);
+    }
+
+    #[test]
+    fn test_grid_states() {
+        fn life(alive: &bool, neighbours: Neighbours<'_, bool>) -> bool {
+            matches!((alive, neighbours.count(&true)), (true, 2) | (_, 3))
+        }
+        let mut blinker = Grid { map: vec![false; 25], width: 5, height: 5 };
+        for x in 1..4 {
+            *blinker.get_val_mut(&Coord::new(x, 2)) = true;
+        }
+        let step = |grid: &Grid<bool>| {
+            let mut automaton = Automaton::new(grid.clone(), Neighbourhood::Adjacent, life);
+            automaton.step();
+            automaton.into_grid()
+        };
+        assert_eq!(brent(&blinker, step), Cycle { start: 0, length: 2 });
+        assert_eq!(nth_state(&blinker, step, 1_000_000_001), step(&blinker));
+        assert_ne!(nth_state(&blinker, step, 1_000_000_001), blinker);
+    }
+}
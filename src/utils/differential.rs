@@ -0,0 +1,170 @@
+use std::cell::Cell;
+use std::env;
+use std::fmt::Debug;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Once;
+
+use super::random::Rng;
+
+const DEFAULT_CASES: usize = 2000;
+const DEFAULT_SEED: u64 = 2025;
+const MAX_SHRINK_STEPS: usize = 10_000;
+
+static QUIET_HOOK: Once = Once::new();
+
+thread_local! {
+    static QUIET: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Differential test between a simple reference solver and the real
+/// solution. Both get the same randomly generated inputs and the first
+/// input on which they disagree (or either of them panics) is shrunk to a
+/// minimal failing one before panicking with it.
+///
+/// Number of cases and the seed can be overridden with the
+/// `DIFFERENTIAL_CASES` and `DIFFERENTIAL_SEED` environment variables.
+pub struct Differential<I, G, S> {
+    pub cases: usize,
+    pub seed: u64,
+    generate: G,
+    shrink: S,
+    _input: std::marker::PhantomData<I>
+}
+
+impl<I, G, S> Differential<I, G, S>
+where
+    I: Clone + Debug,
+    G: Fn(&mut Rng) -> I,
+    S: Fn(&I) -> Vec<I>
+{
+    pub fn new(generate: G, shrink: S) -> Self {
+        let cases = env::var("DIFFERENTIAL_CASES").ok().and_then(|c| c.parse().ok()).unwrap_or(DEFAULT_CASES);
+        let seed = env::var("DIFFERENTIAL_SEED").ok().and_then(|s| s.parse().ok()).unwrap_or(DEFAULT_SEED);
+        Differential { cases, seed, generate, shrink, _input: std::marker::PhantomData }
+    }
+
+    pub fn check<R>(&self, reference: impl Fn(&I) -> R, solution: impl Fn(&I) -> R)
+    where R: PartialEq + Debug
+    {
+        let fails = |input: &I| -> Option<String> {
+            let outcome = panic::catch_unwind(AssertUnwindSafe(|| (reference(input), solution(input))));
+            match outcome {
+                Ok((expected, actual)) if expected == actual => None,
+                Ok((expected, actual)) => Some(format!("expected {:?}, got {:?}", expected, actual)),
+                Err(_) => Some("solver panicked".to_owned())
+            }
+        };
+
+        let mut rng = Rng::new(self.seed);
+        for case in 0..self.cases {
+            let input = (self.generate)(&mut rng);
+            let Some(reason) = Self::quietly(|| fails(&input)) else {
+                continue;
+            };
+            let (minimal, reason) = Self::quietly(|| self.minimize(input, reason, &fails));
+            panic!(
+                "Case {} (seed {}) failed: {}\nMinimal input: {:#?}",
+                case, self.seed, reason, minimal
+            );
+        }
+    }
+
+    /// Greedily takes the first smaller input that still fails, until none
+    /// of the shrunk inputs fail anymore. Steps are capped in case the
+    /// shrinker returns the input itself.
+    fn minimize(&self, mut input: I, mut reason: String, fails: &impl Fn(&I) -> Option<String>) -> (I, String) {
+        'shrinking: for _ in 0..MAX_SHRINK_STEPS {
+            for candidate in (self.shrink)(&input) {
+                if let Some(new_reason) = fails(&candidate) {
+                    input = candidate;
+                    reason = new_reason;
+                    continue 'shrinking;
+                }
+            }
+            break;
+        }
+        (input, reason)
+    }
+
+    /// Runs the closure without printing the messages of caught panics.
+    /// The hook is installed once and only silences the current thread, so
+    /// tests running in parallel still print their panics.
+    fn quietly<T>(f: impl FnOnce() -> T) -> T {
+        QUIET_HOOK.call_once(|| {
+            let hook = panic::take_hook();
+            panic::set_hook(Box::new(move |info| {
+                if !QUIET.get() {
+                    hook(info);
+                }
+            }));
+        });
+        QUIET.set(true);
+        let result = f();
+        QUIET.set(false);
+        result
+    }
+}
+
+/// Smaller versions of the vector: with halves and single elements removed
+/// and with each element shrunk on it's own.
+pub fn shrink_vec<T: Clone>(items: &[T], shrink_item: impl Fn(&T) -> Vec<T>) -> Vec<Vec<T>> {
+    let mut candidates = vec![];
+    if items.len() > 1 {
+        let half = items.len() / 2;
+        candidates.push(items[..half].to_vec());
+        candidates.push(items[half..].to_vec());
+    }
+    for i in 0..items.len() {
+        let mut smaller = items.to_vec();
+        smaller.remove(i);
+        candidates.push(smaller);
+    }
+    for (i, item) in items.iter().enumerate() {
+        for shrunk in shrink_item(item) {
+            let mut smaller = items.to_vec();
+            smaller[i] = shrunk;
+            candidates.push(smaller);
+        }
+    }
+    candidates
+}
+
+pub fn shrink_usize(n: &usize) -> Vec<usize> {
+    match *n {
+        0 => vec![],
+        1 => vec![0],
+        n => vec![0, n / 2, n - 1]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_passing_property() {
+        let differential = Differential::new(
+            |rng: &mut Rng| (0..rng.range(0..=10)).map(|_| rng.range(0..=100)).collect::<Vec<_>>(),
+            |v: &Vec<usize>| shrink_vec(v, shrink_usize)
+        );
+        differential.check(
+            |v| v.iter().sum::<usize>(),
+            |v| v.iter().copied().reduce(|sum, n| sum + n).unwrap_or(0)
+        );
+    }
+
+    #[test]
+    fn test_shrinks_to_minimal_input() {
+        let differential = Differential::new(
+            |rng: &mut Rng| (0..rng.range(0..=10)).map(|_| rng.range(0..=100)).collect::<Vec<_>>(),
+            |v: &Vec<usize>| shrink_vec(v, shrink_usize)
+        );
+        // Buggy solution ignores numbers above 50
+        let result = panic::catch_unwind(|| differential.check(
+            |v| v.iter().sum::<usize>(),
+            |v| v.iter().filter(|n| **n <= 50).sum::<usize>()
+        ));
+        let message = *result.unwrap_err().downcast::<String>().unwrap();
+        assert!(message.contains("Minimal input: [\n    51,\n]"), "{}", message);
+    }
+}
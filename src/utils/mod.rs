@@ -1,6 +1,10 @@
 pub mod tree;
 pub mod digits_iterator;
 pub mod coordinate_system;
+pub mod random;
+pub mod cycle;
+#[cfg(test)]
+pub mod differential;
 
 use std::io;
 
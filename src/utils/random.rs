@@ -0,0 +1,78 @@
+use std::ops::RangeInclusive;
+
+/// Small seedable pseudo random generator (SplitMix64). Good enough for
+/// generating puzzle inputs and it keeps the runs reproducible by seed.
+#[derive(Clone, Debug)]
+pub struct Rng {
+    state: u64
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    /// Random number in the inclusive range.
+    pub fn range(&mut self, range: RangeInclusive<usize>) -> usize {
+        let (start, end) = (*range.start(), *range.end());
+        assert!(start <= end, "Empty range {}..={}", start, end);
+        let span = (end - start) as u64;
+        if span == u64::MAX {
+            return self.next_u64() as usize;
+        }
+        start + (self.next_u64() % (span + 1)) as usize
+    }
+
+    /// Random signed number in the inclusive range.
+    pub fn range_isize(&mut self, range: RangeInclusive<isize>) -> isize {
+        let (start, end) = (*range.start(), *range.end());
+        let offset = self.range(0..=end.abs_diff(start));
+        start.wrapping_add_unsigned(offset)
+    }
+
+    /// Returns true with the probability of `numerator / denominator`.
+    pub fn chance(&mut self, numerator: usize, denominator: usize) -> bool {
+        self.range(1..=denominator) <= numerator
+    }
+
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.range(0..=items.len() - 1)]
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.range(0..=i);
+            items.swap(i, j);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_range() {
+        let mut rng = Rng::new(42);
+        for _ in 0..1000 {
+            assert!((3..=7).contains(&rng.range(3..=7)));
+            assert!((-5..=5).contains(&rng.range_isize(-5..=5)));
+        }
+        assert_eq!(rng.range(4..=4), 4);
+    }
+
+    #[test]
+    fn test_reproducible() {
+        let lhs: Vec<u64> = (0..10).scan(Rng::new(7), |rng, _| Some(rng.next_u64())).collect();
+        let rhs: Vec<u64> = (0..10).scan(Rng::new(7), |rng, _| Some(rng.next_u64())).collect();
+        assert_eq!(lhs, rhs);
+    }
+}